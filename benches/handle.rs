@@ -0,0 +1,126 @@
+//! Benchmarks of the middleware overhead, guarding the simplest configuration (static token, no caching,
+//! no filter) against regressions as options are added.
+//!
+//! Run with `cargo bench`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use http::Extensions;
+use reqwest_auth::AuthorizationHeaderMiddleware;
+use reqwest_middleware::reqwest::{Request, Response};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next};
+use std::sync::Arc;
+use token_source::TokenSource;
+
+#[derive(Debug)]
+struct StaticTokenSource(String);
+
+impl StaticTokenSource {
+    fn new() -> Arc<Self> {
+        Arc::new(Self("my-token".to_string()))
+    }
+
+    /// A token the size of a typical JWT, for which formatting the header value is not negligible.
+    fn jwt_sized() -> Arc<Self> {
+        Arc::new(Self("x".repeat(1200)))
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for StaticTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.0.clone())
+    }
+}
+
+/// A terminal middleware answering all the requests, without sending anything over the network.
+struct Terminal;
+
+#[async_trait::async_trait]
+impl Middleware for Terminal {
+    async fn handle(
+        &self,
+        _req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        Ok(Response::from(http::Response::new("")))
+    }
+}
+
+fn client(auth_middleware: Option<AuthorizationHeaderMiddleware>) -> ClientWithMiddleware {
+    let mut builder = ClientBuilder::new(reqwest::Client::new());
+    if let Some(auth_middleware) = auth_middleware {
+        builder = builder.with(auth_middleware);
+    }
+    builder.with(Terminal).build()
+}
+
+fn bench_handle(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let clients = [
+        ("baseline", client(None)),
+        (
+            "static_token",
+            client(Some(AuthorizationHeaderMiddleware::from(StaticTokenSource::new()))),
+        ),
+        (
+            "static_token_with_scheme",
+            client(Some(
+                AuthorizationHeaderMiddleware::builder(StaticTokenSource::new())
+                    .scheme("Bearer")
+                    .build(),
+            )),
+        ),
+        (
+            "jwt_sized_token_with_scheme",
+            client(Some(
+                AuthorizationHeaderMiddleware::builder(StaticTokenSource::jwt_sized())
+                    .scheme("Bearer")
+                    .build(),
+            )),
+        ),
+        (
+            "filtered",
+            client(Some(
+                AuthorizationHeaderMiddleware::builder(StaticTokenSource::new())
+                    .scheme("Bearer")
+                    .skip_loopback(true)
+                    .allowed_hosts(["example.com"])
+                    .skip_if_cookie("session")
+                    .build(),
+            )),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("handle");
+    for (name, client) in &clients {
+        group.bench_function(*name, |b| {
+            b.to_async(&runtime)
+                .iter(|| async { client.get("https://example.com/items").send().await.unwrap() })
+        });
+    }
+    group.finish();
+}
+
+/// Requests alternating between targets (method, host and path) with an unchanged token: the last header value is
+/// reused whatever the target, as it only depends on the scheme and the token.
+fn bench_handle_alternating_targets(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let client = client(Some(
+        AuthorizationHeaderMiddleware::builder(StaticTokenSource::jwt_sized())
+            .scheme("Bearer")
+            .build(),
+    ));
+
+    let mut group = c.benchmark_group("handle_alternating_targets");
+    group.bench_function("jwt_sized_token_with_scheme", |b| {
+        b.to_async(&runtime).iter(|| async {
+            client.get("https://example.com/items").send().await.unwrap();
+            client.post("https://api.example.com/orders").send().await.unwrap();
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_handle, bench_handle_alternating_targets);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+//! Feeds arbitrary tokens (and schemes) through the header construction of the middleware.
+//!
+//! The middleware must never panic: it either sets a valid header or fails with a clean error.
+
+#![no_main]
+
+use http::Extensions;
+use libfuzzer_sys::fuzz_target;
+use reqwest_auth::AuthorizationHeaderMiddleware;
+use reqwest_middleware::reqwest::header::AUTHORIZATION;
+use reqwest_middleware::reqwest::{Request, Response};
+use reqwest_middleware::{ClientBuilder, Middleware, Next};
+use std::sync::Arc;
+use token_source::TokenSource;
+
+#[derive(Debug)]
+struct FuzzTokenSource {
+    token: String,
+}
+
+#[async_trait::async_trait]
+impl TokenSource for FuzzTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.token.clone())
+    }
+}
+
+/// Checks the header set by the middleware, answering without sending anything over the network.
+struct CheckMiddleware;
+
+#[async_trait::async_trait]
+impl Middleware for CheckMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        assert!(req.headers().get(AUTHORIZATION).is_some());
+        Ok(Response::from(http::Response::new("")))
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    // An optional scheme comes before the first nul byte, the rest is the token
+    let (scheme, token) = match data.iter().position(|b| *b == 0) {
+        Some(i) => (Some(String::from_utf8_lossy(&data[..i]).into_owned()), &data[i + 1..]),
+        None => (None, data),
+    };
+    let ts = Arc::new(FuzzTokenSource {
+        token: String::from_utf8_lossy(token).into_owned(),
+    });
+    let mut builder = AuthorizationHeaderMiddleware::builder(ts);
+    if let Some(scheme) = scheme {
+        builder = builder.scheme(scheme);
+    }
+    let client = ClientBuilder::new(reqwest::Client::default())
+        .with(builder.build())
+        .with(CheckMiddleware)
+        .build();
+
+    // Either the header was valid and checked, or the middleware failed cleanly
+    let _ = futures::executor::block_on(client.get("https://example.com").send());
+});
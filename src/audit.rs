@@ -0,0 +1,49 @@
+use reqwest_middleware::reqwest::header::HeaderName;
+use std::sync::Arc;
+
+/// Observes where credentials were placed, see [on_authorized](crate::AuthorizationHeaderMiddlewareBuilder::on_authorized).
+pub(crate) type AuditHook = Arc<dyn Fn(&AuthAudit<'_>) + Send + Sync>;
+
+/// AuthAudit
+///
+/// Where the credentials of an authorized request went: its host and the names of the headers set by the
+/// middleware. No secret material (e.g header values) is exposed.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::AuthorizationHeaderMiddleware;
+///  # use std::sync::Arc;
+///  # use token_source::TokenSource;
+///  # #[derive(Debug)]
+///  # struct MyTokenSource;
+///  # #[async_trait::async_trait]
+///  # impl TokenSource for MyTokenSource {
+///  #   async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///  #      Ok("my-token".to_string())
+///  #   }
+///  # }
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(MyTokenSource))
+///    .on_authorized(|audit| {
+///      println!("Credentials sent to {:?} in {:?}", audit.host(), audit.header_names());
+///    })
+///    .build();
+/// ```
+#[derive(Debug)]
+pub struct AuthAudit<'a> {
+    pub(crate) host: Option<&'a str>,
+    pub(crate) header_names: &'a [HeaderName],
+}
+
+impl AuthAudit<'_> {
+    /// Returns the effective host of the request, if any.
+    pub fn host(&self) -> Option<&str> {
+        self.host
+    }
+
+    /// Returns the names of the headers set with credentials.
+    pub fn header_names(&self) -> &[HeaderName] {
+        self.header_names
+    }
+}
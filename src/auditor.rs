@@ -0,0 +1,207 @@
+use http::Extensions;
+use reqwest_middleware::reqwest::header::{HeaderMap, HeaderName};
+use reqwest_middleware::reqwest::{Request, Response, Url};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next};
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+use crate::host;
+use crate::AuthorizationHeaderMiddleware;
+
+/// AuthLeak
+///
+/// A request to a host outside the allowed ones, to which the middleware sent credentials, found by an
+/// [AuthAuditor]. No secret material (e.g header values) is exposed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthLeak {
+    url: Url,
+    header_names: Vec<HeaderName>,
+}
+
+impl AuthLeak {
+    /// Returns the url of the request.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the names of the headers set (or changed) by the middleware, in alphabetical order.
+    pub fn header_names(&self) -> &[HeaderName] {
+        &self.header_names
+    }
+}
+
+impl Display for AuthLeak {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "credentials sent to {} in {:?}", self.url, self.header_names)
+    }
+}
+
+/// AuthAuditor
+///
+/// Drives requests through a middleware, without sending anything over the network, to assert that credentials
+/// are only sent to the allowed hosts. This makes credential leak regression tests easy to write.
+///
+/// Any header set or changed by the middleware counts as credentials (e.g the main header, its mirrors and the
+/// secondary and additional headers). Hosts are compared case insensitively to the host of the request url, as exact
+/// names (IPv6 literals as addresses, with or without brackets).
+/// Requests failing in the middleware (e.g when the token source does) are never sent, so they never leak.
+///
+/// Available with the `testing` feature.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthAuditor, AuthorizationHeaderMiddleware, IdentityTokenSource};
+///  use std::sync::Arc;
+///
+///  # async_std::task::block_on(async {
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(IdentityTokenSource::new("my-token")))
+///    .allowed_hosts(["api.example.com"])
+///    .build();
+///
+///  let client = reqwest::Client::new();
+///  let requests = ["https://api.example.com/items", "https://cdn.example.com/logo.png"]
+///    .map(|url| client.get(url).build().unwrap());
+///  AuthAuditor::new(auth_middleware)
+///    .allow_host("api.example.com")
+///    .assert_no_leaks(requests)
+///    .await;
+///  # });
+/// ```
+pub struct AuthAuditor {
+    client: ClientWithMiddleware,
+    captured: Arc<Mutex<Option<HeaderMap>>>,
+    allowed_hosts: Vec<String>,
+}
+
+impl AuthAuditor {
+    /// Creates an auditor of the given middleware, allowing no host.
+    pub fn new(auth_middleware: AuthorizationHeaderMiddleware) -> Self {
+        let captured = Arc::new(Mutex::new(None));
+        let client = ClientBuilder::new(Default::default())
+            .with(auth_middleware)
+            .with(Capture(captured.clone()))
+            .build();
+        Self {
+            client,
+            captured,
+            allowed_hosts: Vec::new(),
+        }
+    }
+
+    /// Allows credentials to be sent to the given host.
+    pub fn allow_host(mut self, host: impl Into<String>) -> Self {
+        self.allowed_hosts.push(host.into());
+        self
+    }
+
+    /// Drives the given requests through the middleware, one at a time, and returns the ones that leaked
+    /// credentials.
+    pub async fn audit(&self, requests: impl IntoIterator<Item = Request>) -> Vec<AuthLeak> {
+        let mut leaks = Vec::new();
+        for req in requests {
+            let url = req.url().clone();
+            let original = req.headers().clone();
+            *self.captured.lock().unwrap() = None;
+            if self.client.execute(req).await.is_err() {
+                continue;
+            }
+            let Some(sent) = self.captured.lock().unwrap().take() else {
+                continue;
+            };
+            let allowed = url
+                .host_str()
+                .is_some_and(|host| self.allowed_hosts.iter().any(|allowed| host::same_host(allowed, host)));
+            if allowed {
+                continue;
+            }
+            let mut header_names: Vec<_> = sent
+                .keys()
+                .filter(|name| {
+                    let before: Vec<_> = original.get_all(*name).iter().collect();
+                    let after: Vec<_> = sent.get_all(*name).iter().collect();
+                    before != after
+                })
+                .cloned()
+                .collect();
+            header_names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+            if !header_names.is_empty() {
+                leaks.push(AuthLeak { url, header_names });
+            }
+        }
+        leaks
+    }
+
+    /// Drives the given requests through the middleware, one at a time.
+    ///
+    /// # Panics
+    ///
+    /// If credentials were sent to a host outside the allowed ones.
+    pub async fn assert_no_leaks(&self, requests: impl IntoIterator<Item = Request>) {
+        let leaks = self.audit(requests).await;
+        if !leaks.is_empty() {
+            let leaks: Vec<_> = leaks.iter().map(ToString::to_string).collect();
+            panic!("credentials leaked to unexpected hosts:\n{}", leaks.join("\n"));
+        }
+    }
+}
+
+/// A terminal middleware keeping the headers of the last request, instead of sending it.
+struct Capture(Arc<Mutex<Option<HeaderMap>>>);
+
+#[async_trait::async_trait]
+impl Middleware for Capture {
+    async fn handle(
+        &self,
+        req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        *self.0.lock().unwrap() = Some(req.headers().clone());
+        Ok(Response::from(http::Response::new("")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::header::{HeaderName, AUTHORIZATION};
+    use std::sync::Arc;
+
+    use super::AuthAuditor;
+    use crate::sources::mock::MockTokenSource;
+    use crate::AuthorizationHeaderMiddleware;
+
+    #[async_std::test]
+    async fn test_auditor() {
+        // Given - a middleware without host restriction, and one bound to the allowed host
+        let ts = Arc::new(MockTokenSource::new().then_token("my-token"));
+        let client = reqwest::Client::new();
+        let requests = || {
+            ["https://api.example.com/items", "https://cdn.example.com/logo.png"]
+                .map(|url| client.get(url).header("x-request-id", "42").build().unwrap())
+        };
+        let unrestricted = AuthAuditor::new(
+            AuthorizationHeaderMiddleware::builder(ts.clone())
+                .mirror_header(HeaderName::from_static("x-auth"))
+                .build(),
+        )
+        .allow_host("API.example.com");
+        let restricted = AuthAuditor::new(
+            AuthorizationHeaderMiddleware::builder(ts)
+                .allowed_hosts(["api.example.com"])
+                .build(),
+        )
+        .allow_host("api.example.com");
+
+        // When - auditing requests to allowed and unexpected hosts
+        // Then - the credentials sent to unexpected hosts are reported, in all their headers
+        let leaks = unrestricted.audit(requests()).await;
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].url().as_str(), "https://cdn.example.com/logo.png");
+        assert_eq!(leaks[0].header_names(), [AUTHORIZATION, HeaderName::from_static("x-auth")]);
+        assert!(!leaks[0].to_string().contains("my-token"));
+
+        // Then - no leak is reported when credentials are only sent to the allowed hosts
+        restricted.assert_no_leaks(requests()).await;
+    }
+}
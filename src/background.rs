@@ -0,0 +1,74 @@
+//! Proactive refreshes of the cached token on a fixed schedule, see
+//! [background_refresh_interval](crate::AuthorizationHeaderMiddlewareBuilder::background_refresh_interval).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::task::AbortHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::cache::Cache;
+use crate::Source;
+
+/// The background task refreshing the cached token, aborted when dropped (i.e with the middleware).
+pub(crate) struct BackgroundRefresh {
+    interval: Duration,
+    started: AtomicBool,
+    task: Mutex<Option<AbortHandle>>,
+}
+
+impl BackgroundRefresh {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            started: AtomicBool::new(false),
+            task: Mutex::new(None),
+        }
+    }
+
+    /// Spawns the task on the current tokio runtime, unless it is already running.
+    ///
+    /// Without a runtime, this is a no-op: the task is spawned by the next call within one.
+    pub(crate) fn start(&self, cache: &Arc<Cache>, source: &Arc<RwLock<Source>>) {
+        if self.started.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        if self.started.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        // The task only holds weak references, so that it never keeps the cache nor the source alive
+        let cache = Arc::downgrade(cache);
+        let source = Arc::downgrade(source);
+        // The first refresh happens one interval after the start, the initial token being fetched by the requests
+        let start = tokio::time::Instant::now() + self.interval;
+        let mut interval = tokio::time::interval_at(start, self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let task = runtime.spawn(async move {
+            loop {
+                interval.tick().await;
+                let (Some(cache), Some(source)) = (cache.upgrade(), source.upgrade()) else {
+                    return;
+                };
+                let Source::Plain(ts) = source.read().unwrap().clone() else {
+                    return;
+                };
+                drop(source);
+                if let Err(err) = cache.refresh_scheduled(&ts).await {
+                    log::warn!("Background token refresh failed: {err}");
+                }
+            }
+        });
+        *self.task.lock().unwrap() = Some(task.abort_handle());
+    }
+}
+
+impl Drop for BackgroundRefresh {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+}
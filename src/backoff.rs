@@ -0,0 +1,128 @@
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// Backoff
+///
+/// How long the middleware waits before each retry, set for all its retries with
+/// [backoff](crate::AuthorizationHeaderMiddlewareBuilder::backoff).
+///
+/// Implement it for custom behaviors (e.g jitter, or ceilings per retry), or use the provided [ExponentialBackoff]
+/// and [ConstantBackoff]. The waits are slept on the tokio timer of the runtime driving reqwest: backoffs returning
+/// zero delays retry right away, e.g for tests driving the time with a [Clock](crate::Clock).
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::Backoff;
+///  use std::sync::Mutex;
+///  use std::time::Duration;
+///
+///  // Waits between 50% and 100% of an exponential backoff (full jitter would go down to zero)
+///  #[derive(Debug)]
+///  struct JitteredBackoff {
+///    rng: Mutex<fastrand::Rng>,
+///  }
+///
+///  impl Backoff for JitteredBackoff {
+///    fn delay(&self, retry: u32) -> Duration {
+///      let delay = Duration::from_millis(100) * 2u32.saturating_pow(retry).min(64);
+///      delay.mul_f64(0.5 + self.rng.lock().unwrap().f64() / 2.0)
+///    }
+///  }
+/// ```
+pub trait Backoff: Send + Sync + Debug {
+    /// Returns how long to wait before the given retry (starting at zero).
+    fn delay(&self, retry: u32) -> Duration;
+}
+
+/// ExponentialBackoff
+///
+/// A [Backoff] waiting for an initial delay before the first retry, doubled at each following one, up to a maximum
+/// delay.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{Backoff, ExponentialBackoff};
+///  use std::time::Duration;
+///
+///  // Wait 100ms, 200ms, 400ms, then 500ms
+///  let backoff = ExponentialBackoff::new(Duration::from_millis(100)).max_delay(Duration::from_millis(500));
+///  assert_eq!(backoff.delay(3), Duration::from_millis(500));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    initial: Duration,
+    max_delay: Duration,
+}
+
+impl ExponentialBackoff {
+    /// Creates a backoff waiting for the given delay before the first retry, without maximum delay.
+    pub const fn new(initial: Duration) -> Self {
+        Self {
+            initial,
+            max_delay: Duration::MAX,
+        }
+    }
+
+    /// Sets the maximum wait before a retry.
+    ///
+    /// By default, the delays are not capped.
+    pub const fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+}
+
+impl Backoff for ExponentialBackoff {
+    fn delay(&self, retry: u32) -> Duration {
+        self.initial
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay)
+    }
+}
+
+/// ConstantBackoff
+///
+/// A [Backoff] waiting for the same delay before each retry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstantBackoff {
+    delay: Duration,
+}
+
+impl ConstantBackoff {
+    /// Creates a backoff waiting for the given delay before each retry.
+    pub const fn new(delay: Duration) -> Self {
+        Self { delay }
+    }
+}
+
+impl Backoff for ConstantBackoff {
+    fn delay(&self, _retry: u32) -> Duration {
+        self.delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Backoff, ConstantBackoff, ExponentialBackoff};
+
+    #[test]
+    fn test_backoffs() {
+        // Given - an exponential backoff, capped at 1s
+        let backoff = ExponentialBackoff::new(Duration::from_millis(300)).max_delay(Duration::from_secs(1));
+
+        // Then - the delay is doubled at each retry, up to the maximum delay
+        let delays: Vec<_> = (0..4).map(|retry| backoff.delay(retry)).collect();
+        assert_eq!(delays, [300, 600, 1000, 1000].map(Duration::from_millis));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(1));
+
+        // Given - a constant backoff
+        let backoff = ConstantBackoff::new(Duration::from_millis(300));
+
+        // Then - the delay is the same at each retry
+        assert_eq!(backoff.delay(0), backoff.delay(10));
+    }
+}
@@ -0,0 +1,76 @@
+//! Buffering of streaming request bodies, so that the requests can be cloned for retries.
+
+use bytes::{Bytes, BytesMut};
+use http_body::{Body as HttpBody, Frame};
+use reqwest_middleware::reqwest::{Body, Error};
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Reads the given body into memory, up to the given length.
+///
+/// Returns a cloneable body when it fits, or else an equivalent streaming body (replaying the frames read so far).
+pub(crate) async fn buffer(mut body: Body, max_len: usize) -> Result<Result<Body, Body>, Error> {
+    let mut frames = VecDeque::new();
+    let mut len = 0;
+    while let Some(frame) = std::future::poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await {
+        let frame = frame?;
+        len += frame.data_ref().map_or(0, Bytes::len);
+        let is_data = frame.is_data();
+        frames.push_back(frame);
+        // Bodies with trailers cannot be held in a cloneable body
+        if len > max_len || !is_data {
+            return Ok(Err(Body::wrap(Prefixed { frames, rest: body })));
+        }
+    }
+    let mut bytes = BytesMut::with_capacity(len);
+    for frame in frames {
+        if let Ok(data) = frame.into_data() {
+            bytes.extend_from_slice(&data);
+        }
+    }
+    Ok(Ok(Body::from(bytes.freeze())))
+}
+
+/// A body yielding the frames already read from a body, then the rest of it.
+struct Prefixed {
+    frames: VecDeque<Frame<Bytes>>,
+    rest: Body,
+}
+
+impl HttpBody for Prefixed {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>> {
+        match self.frames.pop_front() {
+            Some(frame) => Poll::Ready(Some(Ok(frame))),
+            None => Pin::new(&mut self.rest).poll_frame(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::buffer;
+    use reqwest_middleware::reqwest::Body;
+
+    #[async_std::test]
+    async fn test_buffer() {
+        // Given - a streaming body
+        let body = || Body::wrap("my-payload".to_string());
+        assert!(body().as_bytes().is_none());
+
+        // When - buffering it within the maximum length
+        // Then - it is cloneable
+        let buffered = buffer(body(), 10).await.unwrap().unwrap();
+        assert_eq!(buffered.as_bytes(), Some(&b"my-payload"[..]));
+
+        // When - buffering it beyond the maximum length
+        // Then - it is still streaming, with the same content
+        let streaming = buffer(body(), 4).await.unwrap().unwrap_err();
+        assert!(streaming.as_bytes().is_none());
+        let content = buffer(streaming, usize::MAX).await.unwrap().unwrap();
+        assert_eq!(content.as_bytes(), Some(&b"my-payload"[..]));
+    }
+}
@@ -0,0 +1,942 @@
+use reqwest_middleware::reqwest::Request;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use tokio::sync::Semaphore;
+use tokio::task::AbortHandle;
+
+use crate::claims;
+use crate::latency::LatencyWindow;
+use crate::limit;
+use crate::metrics;
+use crate::telemetry;
+use crate::Clock;
+use crate::FetchReason;
+use crate::ReasonAwareTokenSource;
+use crate::RefreshFailurePolicy;
+use crate::StoredToken;
+use crate::TokenCache;
+
+/// CacheStrategy
+///
+/// How the middleware caches the tokens of its token source, and refreshes them once expired.
+///
+/// The time to live (TTL) of a token is counted from the moment it was fetched, as read from the
+/// [clock](crate::AuthorizationHeaderMiddlewareBuilder::clock) of the middleware. To refresh tokens ahead of
+/// their actual expiry (skew), use a TTL shorter than their lifetime.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::CacheStrategy;
+///  use std::time::Duration;
+///
+///  // Tokens are valid for 5 minutes, refresh them after 4
+///  let strategy = CacheStrategy::Blocking { ttl: Duration::from_secs(240) };
+///
+///  // Keep serving the expired token for up to 1 more minute while it is refreshed
+///  let strategy = CacheStrategy::BackgroundStaleWhileRevalidate {
+///    ttl: Duration::from_secs(240),
+///    max_stale: Duration::from_secs(60),
+///  };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheStrategy {
+    /// Refresh synchronously on expiry: requests wait for the new token.
+    Blocking {
+        /// How long a token is served once fetched.
+        ttl: Duration,
+    },
+    /// Serve the expired token while refreshing it in the background, for up to `max_stale` past its TTL.
+    ///
+    /// The refresh is spawned on the current tokio runtime (the one driving reqwest). Without a runtime, or
+    /// once the token is stale for longer than `max_stale`, the refresh is done synchronously as with
+    /// [Blocking](CacheStrategy::Blocking).
+    BackgroundStaleWhileRevalidate {
+        /// How long a token is served once fetched, before being refreshed.
+        ttl: Duration,
+        /// How long an expired token is still served while being refreshed.
+        max_stale: Duration,
+    },
+    /// Optimistically keep sending the expired token for up to `grace` past its TTL, while refreshing it in the
+    /// background. Requests rejected with a 401 (Unauthorized) status are retried once with the refreshed token.
+    ///
+    /// This is meant to ride out intermittent outages of the token provider, as long as servers accept tokens
+    /// past the TTL. Unlike [BackgroundStaleWhileRevalidate](CacheStrategy::BackgroundStaleWhileRevalidate),
+    /// expired tokens are expected to be rejected at times: only requests which can be cloned for the retry
+    /// get the expired token, others wait for the refreshed one.
+    ///
+    /// <div class="warning">An expired token remains usable by whoever got hold of it for the grace window:
+    /// revoking a token (e.g after a leak) only takes effect once servers reject it.</div>
+    Grace {
+        /// How long a token is served once fetched, before being refreshed.
+        ttl: Duration,
+        /// How long an expired token is still sent while being refreshed.
+        grace: Duration,
+    },
+    /// Fetch a token once, on first use, and serve it until the cache is explicitly
+    /// [invalidated](crate::AuthorizationHeaderMiddleware::invalidate).
+    ///
+    /// This is meant for static tokens (e.g injected at boot), which never change.
+    Forever,
+}
+
+impl CacheStrategy {
+    fn ttl(&self) -> Duration {
+        match self {
+            Self::Blocking { ttl } | Self::BackgroundStaleWhileRevalidate { ttl, .. } | Self::Grace { ttl, .. } => *ttl,
+            Self::Forever => Duration::MAX,
+        }
+    }
+}
+
+/// A token fetched from the token source.
+#[derive(Clone)]
+struct CachedToken {
+    token: TokenValue,
+    fetched_at: Instant,
+    // Unique per fetch, to tell whether a rejected token was replaced since
+    generation: u64,
+}
+
+/// Spreads the expiries of the cached tokens, so that tokens fetched at the same time are not all refreshed at once.
+pub(crate) struct Jitter {
+    max: Duration,
+    rng: Mutex<fastrand::Rng>,
+}
+
+impl Jitter {
+    /// Creates a jitter of up to the given duration, using a random generator initialized with the given seed (if
+    /// any).
+    pub(crate) fn new(max: Duration, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+        Self {
+            max,
+            rng: Mutex::new(rng),
+        }
+    }
+
+    /// Returns how much earlier than its TTL a token expires, at most the given TTL.
+    fn offset(&self, ttl: Duration) -> Duration {
+        self.max.min(ttl).mul_f64(self.rng.lock().unwrap().f64())
+    }
+}
+
+/// CacheStats
+///
+/// The effectiveness of the token caches of the middleware, returned by
+/// [cache_stats](crate::AuthorizationHeaderMiddleware::cache_stats), e.g to tune the TTL of the [cache
+/// strategy](CacheStrategy).
+///
+/// The counts cover all the caches of the middleware (of its token source, of the [additional
+/// headers](crate::AuthorizationHeaderMiddlewareBuilder::header_auth) and of the [cache
+/// keys](crate::AuthorizationHeaderMiddlewareBuilder::cache_key)) since it was built. A hit is a token served from
+/// the cache (or loaded from the [token cache](crate::AuthorizationHeaderMiddlewareBuilder::token_cache)), a miss a
+/// request waiting for a fetch for lack of a fresh token, and a refresh a token fetch made by the cache, whether
+/// for a miss, in the background or on schedule. The requests waiting for the fetch of another one (as a single
+/// fetch is made at a time) are counted apart, as coalesced: they neither got a cached token nor made a fetch.
+///
+/// # How to use
+///
+/// ```rust
+///  # #[derive(Debug)]
+///  # struct MyTokenSource;
+///  # #[async_trait::async_trait]
+///  # impl token_source::TokenSource for MyTokenSource {
+///  #   async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///  #     Ok("my-token".to_string())
+///  #   }
+///  # }
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, CacheStrategy};
+///  use std::sync::Arc;
+///  use std::time::Duration;
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(MyTokenSource))
+///    .cache_strategy(CacheStrategy::Blocking { ttl: Duration::from_secs(60) })
+///    .build();
+///
+///  if let Some(stats) = auth_middleware.cache_stats() {
+///    println!("Token cache: {} hits, {} misses, {} refreshes", stats.hits(), stats.misses(), stats.refreshes());
+///  }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    hits: u64,
+    misses: u64,
+    coalesced: u64,
+    refreshes: u64,
+}
+
+impl CacheStats {
+    /// Returns how many tokens were served from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns how many requests waited for a token fetch, for lack of a fresh cached token.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Returns how many requests waited for the token fetch of another request, rather than making their own.
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced
+    }
+
+    /// Returns how many tokens the cache fetched from the token sources.
+    pub fn refreshes(&self) -> u64 {
+        self.refreshes
+    }
+
+    /// Returns the share of the hits among all the requests (hits, misses and coalesced ones), none before the
+    /// first request.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        match self.hits + self.misses + self.coalesced {
+            0 => None,
+            total => Some(self.hits as f64 / total as f64),
+        }
+    }
+}
+
+/// The counters of the [CacheStats], shared by all the caches of a middleware.
+///
+/// They are only incremented and read, without ordering with other memory accesses: relaxed atomics suffice.
+#[derive(Debug, Default)]
+pub(crate) struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+    refreshes: AtomicU64,
+}
+
+impl CacheCounters {
+    /// Returns the current counts.
+    pub(crate) fn snapshot(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            refreshes: self.refreshes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// RefreshState
+///
+/// A snapshot of the refreshes of the cached token, returned by
+/// [refresh_state](crate::AuthorizationHeaderMiddleware::refresh_state) to diagnose stuck or failing refreshes.
+///
+/// <div class="warning">The error message comes from the token source, and may quote secret material: this is
+/// meant for debugging, not to be logged as is.</div>
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RefreshState {
+    in_flight_since: Option<Instant>,
+    last_error: Option<(Instant, String)>,
+}
+
+impl RefreshState {
+    /// Whether no fetch is in progress.
+    pub fn is_idle(&self) -> bool {
+        self.in_flight_since.is_none()
+    }
+
+    /// Returns when the fetch in progress (if any) started, as read from the clock of the middleware.
+    pub fn in_flight_since(&self) -> Option<Instant> {
+        self.in_flight_since
+    }
+
+    /// Returns when the last fetch failed, along with its error message, unless a fetch succeeded since.
+    pub fn last_error(&self) -> Option<(Instant, &str)> {
+        self.last_error.as_ref().map(|(at, message)| (*at, message.as_str()))
+    }
+}
+
+/// Marks a fetch as in progress, until dropped (even if the fetch is cancelled).
+struct InFlight<'a>(&'a Mutex<RefreshState>);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().in_flight_since = None;
+    }
+}
+
+/// The cached token value, zeroized on drop with the `secrecy` feature.
+#[cfg(feature = "secrecy")]
+pub(crate) type TokenValue = secrecy::SecretString;
+#[cfg(not(feature = "secrecy"))]
+pub(crate) type TokenValue = String;
+
+#[cfg(feature = "secrecy")]
+pub(crate) fn expose(token: &TokenValue) -> String {
+    secrecy::ExposeSecret::expose_secret(token).to_string()
+}
+
+#[cfg(not(feature = "secrecy"))]
+pub(crate) fn expose(token: &TokenValue) -> String {
+    token.clone()
+}
+
+/// Borrows the token value, without copying it.
+#[cfg(feature = "secrecy")]
+pub(crate) fn peek(token: &TokenValue) -> &str {
+    secrecy::ExposeSecret::expose_secret(token)
+}
+
+#[cfg(not(feature = "secrecy"))]
+pub(crate) fn peek(token: &TokenValue) -> &str {
+    token
+}
+
+#[cfg(feature = "secrecy")]
+pub(crate) fn protect(token: String) -> TokenValue {
+    token.into()
+}
+
+#[cfg(not(feature = "secrecy"))]
+pub(crate) fn protect(token: String) -> TokenValue {
+    token
+}
+
+/// The token cache of the middleware, per its [CacheStrategy].
+pub(crate) struct Cache {
+    strategy: CacheStrategy,
+    clock: Arc<dyn Clock>,
+    token: Mutex<Option<CachedToken>>,
+    generation: AtomicU64,
+    // Only locked briefly, never across a fetch, so that reading it does not wait for a refresh
+    state: Mutex<RefreshState>,
+    fetch_limit: Option<Arc<Semaphore>>,
+    // Held while fetching, so that concurrent expired requests trigger a single fetch
+    refresh: Arc<tokio::sync::Mutex<()>>,
+    // The shared storage of the token and its key, if any
+    store: Option<(Arc<dyn TokenCache>, String)>,
+    // Set once cleared, so that the stored token is replaced rather than loaded again
+    bypass_store: AtomicBool,
+    jitter: Option<Arc<Jitter>>,
+    failure_policy: RefreshFailurePolicy,
+    latency: Option<Arc<LatencyWindow>>,
+    counters: Arc<CacheCounters>,
+    // Whether the tokens expire per their JWT expiry (if any) rather than the TTL
+    auto_ttl_from_jwt: bool,
+    // The latest refresh spawned in the background, aborted along with the middleware
+    background: Mutex<Option<AbortHandle>>,
+}
+
+impl Cache {
+    pub(crate) fn new(strategy: CacheStrategy, clock: Arc<dyn Clock>, fetch_limit: Option<Arc<Semaphore>>) -> Self {
+        Self {
+            strategy,
+            clock,
+            token: Mutex::new(None),
+            generation: AtomicU64::new(0),
+            state: Mutex::new(RefreshState::default()),
+            fetch_limit,
+            refresh: Arc::new(tokio::sync::Mutex::new(())),
+            store: None,
+            bypass_store: AtomicBool::new(false),
+            jitter: None,
+            failure_policy: RefreshFailurePolicy::Retain,
+            latency: None,
+            counters: Arc::default(),
+            auto_ttl_from_jwt: false,
+            background: Mutex::new(None),
+        }
+    }
+
+    /// Goes through the given storage under the given key on a miss, see [TokenCache].
+    pub(crate) fn with_store(mut self, store: Arc<dyn TokenCache>, key: String) -> Self {
+        self.store = Some((store, key));
+        self
+    }
+
+    /// Makes the fetched tokens expire earlier than the TTL, by a random offset drawn from the given jitter.
+    pub(crate) fn with_jitter(mut self, jitter: Option<Arc<Jitter>>) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets what to do with the expired token when its refresh fails.
+    pub(crate) fn with_failure_policy(mut self, failure_policy: RefreshFailurePolicy) -> Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Records the durations of the fetches in the given window (if any).
+    pub(crate) fn with_latency(mut self, latency: Option<Arc<LatencyWindow>>) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Counts the hits, misses and refreshes in the given counters, e.g shared with the other caches.
+    pub(crate) fn with_counters(mut self, counters: Arc<CacheCounters>) -> Self {
+        self.counters = counters;
+        self
+    }
+
+    /// Makes the fetched JWT tokens expire per their `exp` claim rather than the TTL, when enabled.
+    pub(crate) fn with_auto_ttl_from_jwt(mut self, auto_ttl_from_jwt: bool) -> Self {
+        self.auto_ttl_from_jwt = auto_ttl_from_jwt;
+        self
+    }
+
+    /// Returns whether the JWT tokens expire per their `exp` claim.
+    pub(crate) fn auto_ttl_from_jwt(&self) -> bool {
+        self.auto_ttl_from_jwt
+    }
+
+    /// Returns the strategy of the cache.
+    pub(crate) fn strategy(&self) -> CacheStrategy {
+        self.strategy
+    }
+
+    /// Returns what is done with the expired token when its refresh fails.
+    pub(crate) fn failure_policy(&self) -> RefreshFailurePolicy {
+        self.failure_policy
+    }
+
+    /// Drops the cached token, so that the next one is fetched from the token source.
+    pub(crate) fn clear(&self) {
+        *self.token.lock().unwrap() = None;
+        self.bypass_store.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns when the cached token (if any) expires, per the TTL of the strategy.
+    pub(crate) fn expiry(&self) -> Option<Instant> {
+        self.cached()?.fetched_at.checked_add(self.strategy.ttl())
+    }
+
+    /// Returns in how long the cached token (if any) expires, zero once expired.
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        Some(self.expiry()?.saturating_duration_since(self.clock.now()))
+    }
+
+    /// Makes the cached token expire in the given time, if it still is the one of the given generation.
+    ///
+    /// The token is re-dated so that it expires then, its stale window (if any) following. Tokens cached
+    /// [forever](CacheStrategy::Forever) are left as is.
+    pub(crate) fn expire_in(&self, generation: u64, remaining: Duration) {
+        if matches!(self.strategy, CacheStrategy::Forever) {
+            return;
+        }
+        let now = self.clock.now();
+        let ttl = self.strategy.ttl();
+        let fetched_at = match remaining.checked_sub(ttl) {
+            Some(extra) => now.checked_add(extra),
+            None => now.checked_sub(ttl - remaining),
+        };
+        let mut cached = self.token.lock().unwrap();
+        if let (Some(cached), Some(fetched_at)) = (cached.as_mut(), fetched_at) {
+            if cached.generation == generation {
+                cached.fetched_at = fetched_at;
+            }
+        }
+    }
+
+    /// Returns the generation of the cached token, if any.
+    pub(crate) fn generation(&self) -> Option<u64> {
+        self.cached().map(|cached| cached.generation)
+    }
+
+    /// Returns a snapshot of the refreshes.
+    pub(crate) fn refresh_state(&self) -> RefreshState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Whether expired tokens may be sent, to be retried on rejection (see [CacheStrategy::Grace]).
+    pub(crate) fn has_grace(&self) -> bool {
+        matches!(self.strategy, CacheStrategy::Grace { .. })
+    }
+
+    /// Returns the cached token, or fetches one from the token source per the strategy,
+    /// along with its generation if the token is expired.
+    ///
+    /// Expired tokens are only returned when `allow_stale` is set.
+    pub(crate) async fn token(
+        self: &Arc<Self>,
+        ts: &Arc<dyn ReasonAwareTokenSource>,
+        allow_stale: bool,
+    ) -> Result<(String, Option<u64>), Box<dyn std::error::Error + Send + Sync>> {
+        let now = self.clock.now();
+        let cached = self.cached();
+        let reason = match cached {
+            Some(_) => FetchReason::Expired,
+            None => FetchReason::Initial,
+        };
+        if let Some(cached) = cached {
+            let age = now.saturating_duration_since(cached.fetched_at);
+            if age < self.strategy.ttl() {
+                self.hit();
+                return Ok((expose(&cached.token), None));
+            }
+            let max_stale = match self.strategy {
+                CacheStrategy::BackgroundStaleWhileRevalidate { max_stale, .. } => Some(max_stale),
+                CacheStrategy::Grace { grace, .. } if allow_stale => Some(grace),
+                _ => None,
+            };
+            if max_stale.is_some_and(|max_stale| age < self.strategy.ttl() + max_stale) {
+                if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                    self.refresh_in_background(&runtime, ts, FetchReason::Expired);
+                    self.hit();
+                    return Ok((expose(&cached.token), Some(cached.generation)));
+                }
+            }
+        }
+        self.refresh(ts, reason).await.map(|token| (token, None))
+    }
+
+    /// Returns the cached token whatever its age, along with its generation if it is expired, without ever waiting
+    /// for a fetch: the token is refreshed in the background once expired, or fetched when there is none yet.
+    pub(crate) fn token_now(
+        self: &Arc<Self>,
+        runtime: &tokio::runtime::Handle,
+        ts: &Arc<dyn ReasonAwareTokenSource>,
+    ) -> Option<(String, Option<u64>)> {
+        let fresh = self.is_fresh();
+        let cached = self.cached();
+        match (&cached, fresh) {
+            (_, true) => {}
+            (Some(_), false) => self.refresh_in_background(runtime, ts, FetchReason::Expired),
+            (None, false) => self.refresh_in_background(runtime, ts, FetchReason::Initial),
+        }
+        let cached = cached?;
+        self.hit();
+        Some((expose(&cached.token), (!fresh).then_some(cached.generation)))
+    }
+
+    /// Records a token served from the cache.
+    fn hit(&self) {
+        metrics::cache_hit();
+        telemetry::cache_hit(true);
+        self.counters.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a token missing from the cache.
+    fn miss(&self) {
+        metrics::cache_miss();
+        telemetry::cache_hit(false);
+        self.counters.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a token served once fetched by another request, after waiting for it.
+    fn coalesced(&self) {
+        metrics::cache_miss();
+        telemetry::cache_hit(false);
+        self.counters.coalesced.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn cached(&self) -> Option<CachedToken> {
+        self.token.lock().unwrap().clone()
+    }
+
+    /// Whether the cached token (if any) is within its TTL.
+    fn is_fresh(&self) -> bool {
+        let now = self.clock.now();
+        self.cached()
+            .is_some_and(|cached| now.saturating_duration_since(cached.fetched_at) < self.strategy.ttl())
+    }
+
+    /// Fetches a new token for the given reason, unless another request just did.
+    pub(crate) async fn refresh(
+        &self,
+        ts: &Arc<dyn ReasonAwareTokenSource>,
+        reason: FetchReason,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let _guard = self.refresh.lock().await;
+        if let Some(cached) = self.cached().filter(|_| self.is_fresh()) {
+            self.coalesced();
+            return Ok(expose(&cached.token));
+        }
+        if let Some(token) = self.load(None).await {
+            return Ok(token);
+        }
+        self.miss();
+        self.fetch(ts, reason).await
+    }
+
+    /// Fetches a new token on schedule, whether the cached one expired or not.
+    pub(crate) async fn refresh_scheduled(
+        &self,
+        ts: &Arc<dyn ReasonAwareTokenSource>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let _guard = self.refresh.lock().await;
+        self.fetch(ts, FetchReason::Expired).await
+    }
+
+    /// Fetches a new token to replace the rejected one of the given generation, unless another request just did.
+    pub(crate) async fn refresh_rejected(
+        &self,
+        ts: &Arc<dyn ReasonAwareTokenSource>,
+        generation: u64,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let _guard = self.refresh.lock().await;
+        let cached = self.cached();
+        let replaced = cached.as_ref().filter(|cached| cached.generation != generation);
+        if let Some(cached) = replaced.filter(|_| self.is_fresh()) {
+            self.coalesced();
+            return Ok(expose(&cached.token));
+        }
+        // Another process may have replaced the rejected token already
+        let rejected = cached
+            .filter(|cached| cached.generation == generation)
+            .map(|cached| expose(&cached.token));
+        if let Some(token) = self.load(rejected.as_deref()).await {
+            return Ok(token);
+        }
+        self.miss();
+        self.fetch(ts, FetchReason::Rejected).await
+    }
+
+    /// Returns the cached token, or fetches one with the given fetch once expired (none meaning the request is
+    /// sent without authorization, which is not cached).
+    ///
+    /// Unlike [token](Self::token), expired tokens are never served: the fetch borrows the request, so it cannot be
+    /// done in the background.
+    pub(crate) async fn token_with(
+        &self,
+        fetch: impl Future<Output = Result<Option<String>, Box<dyn std::error::Error + Send + Sync>>>,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(cached) = self.cached().filter(|_| self.is_fresh()) {
+            self.hit();
+            return Ok(Some(expose(&cached.token)));
+        }
+        let _guard = self.refresh.lock().await;
+        if let Some(cached) = self.cached().filter(|_| self.is_fresh()) {
+            self.coalesced();
+            return Ok(Some(expose(&cached.token)));
+        }
+        if let Some(token) = self.load(None).await {
+            return Ok(Some(token));
+        }
+        self.miss();
+        self.fetch_with(fetch).await
+    }
+
+    /// Loads the stored token (if any, and unless it is expired or the given rejected one) into the cache.
+    async fn load(&self, rejected: Option<&str>) -> Option<String> {
+        let (store, key) = self.store.as_ref()?;
+        if self.bypass_store.load(Ordering::Relaxed) {
+            return None;
+        }
+        let stored = match store.load(key).await {
+            Ok(stored) => stored?,
+            Err(e) => {
+                log::warn!("The token cache failed to load the token: {e}");
+                return None;
+            }
+        };
+        let now = SystemTime::now();
+        if stored.is_expired_at(now) || rejected == Some(stored.token.as_str()) {
+            return None;
+        }
+        // Backdate the token, so that it expires along with the stored one
+        let current = self.clock.now();
+        let fetched_at = match stored.expires_at {
+            Some(expires_at) => {
+                let remaining = expires_at.duration_since(now).unwrap_or_default();
+                current
+                    .checked_sub(self.strategy.ttl().saturating_sub(remaining))
+                    .unwrap_or(current)
+            }
+            None => current,
+        };
+        *self.token.lock().unwrap() = Some(CachedToken {
+            token: protect(stored.token.clone()),
+            fetched_at,
+            generation: self.generation.fetch_add(1, Ordering::Relaxed),
+        });
+        self.hit();
+        Some(stored.token)
+    }
+
+    /// Saves the fetched token in the storage (if any), for the other users of the storage, expiring after the given
+    /// lifetime.
+    async fn save(&self, token: &str, lifetime: Duration) {
+        let Some((store, key)) = &self.store else {
+            return;
+        };
+        let expires_at = match self.strategy {
+            CacheStrategy::Forever => None,
+            _ => SystemTime::now().checked_add(lifetime),
+        };
+        let stored = StoredToken {
+            token: token.to_string(),
+            expires_at,
+        };
+        match store.save(key, &stored).await {
+            Ok(()) => self.bypass_store.store(false, Ordering::Relaxed),
+            Err(e) => log::warn!("The token cache failed to save the token: {e}"),
+        }
+    }
+
+    /// Fetches a new token and caches it.
+    async fn fetch(
+        &self,
+        ts: &Arc<dyn ReasonAwareTokenSource>,
+        reason: FetchReason,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let token = self.fetch_with(async { ts.token_for(reason).await.map(Some) }).await?;
+        // Plain sources always provide a token
+        Ok(token.unwrap_or_default())
+    }
+
+    /// Fetches a new token with the given fetch and caches it (if any).
+    async fn fetch_with(
+        &self,
+        fetch: impl Future<Output = Result<Option<String>, Box<dyn std::error::Error + Send + Sync>>>,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        self.counters.refreshes.fetch_add(1, Ordering::Relaxed);
+        self.state.lock().unwrap().in_flight_since = Some(self.clock.now());
+        let in_flight = InFlight(&self.state);
+        let fetch = LatencyWindow::measure(self.latency.as_deref(), metrics::fetch(fetch));
+        let result = limit::fetch(self.fetch_limit.as_deref(), fetch).await;
+        drop(in_flight);
+        let token = result.inspect_err(|e| {
+            if self.failure_policy == RefreshFailurePolicy::Clear && !self.is_fresh() {
+                *self.token.lock().unwrap() = None;
+            }
+            self.state.lock().unwrap().last_error = Some((self.clock.now(), e.to_string()));
+        })?;
+        self.state.lock().unwrap().last_error = None;
+        if let Some(token) = &token {
+            // Backdate the token by the jitter (if any), so that it expires earlier
+            let offset = match (&self.jitter, self.strategy) {
+                (_, CacheStrategy::Forever) | (None, _) => Duration::ZERO,
+                (Some(jitter), strategy) => jitter.offset(strategy.ttl()),
+            };
+            let now = self.clock.now();
+            let ttl = self.strategy.ttl();
+            let (fetched_at, lifetime) = match self.jwt_lifetime(token) {
+                // Re-date the token, so that it expires along with the JWT (by the jitter earlier)
+                Some(lifetime) => {
+                    let lifetime = lifetime.saturating_sub(offset);
+                    let fetched_at = match lifetime.checked_sub(ttl) {
+                        Some(extra) => now.checked_add(extra),
+                        None => now.checked_sub(ttl - lifetime),
+                    };
+                    (fetched_at, lifetime)
+                }
+                None => (now.checked_sub(offset), ttl.saturating_sub(offset)),
+            };
+            *self.token.lock().unwrap() = Some(CachedToken {
+                token: protect(token.clone()),
+                fetched_at: fetched_at.unwrap_or(now),
+                generation: self.generation.fetch_add(1, Ordering::Relaxed),
+            });
+            self.save(token, lifetime).await;
+        }
+        Ok(token)
+    }
+
+    /// Returns in how long the given token expires per its JWT expiry, with the auto TTL option, none when it is not
+    /// a JWT with an `exp` claim (or for the tokens cached forever).
+    fn jwt_lifetime(&self, token: &str) -> Option<Duration> {
+        if !self.auto_ttl_from_jwt || matches!(self.strategy, CacheStrategy::Forever) {
+            return None;
+        }
+        claims::expires_in(token)
+    }
+
+    /// Spawns a refresh for the given reason, unless one is already in progress.
+    ///
+    /// Errors are not reported: the stale token keeps being served, and the next request will try again.
+    fn refresh_in_background(
+        self: &Arc<Self>,
+        runtime: &tokio::runtime::Handle,
+        ts: &Arc<dyn ReasonAwareTokenSource>,
+        reason: FetchReason,
+    ) {
+        let Ok(guard) = self.refresh.clone().try_lock_owned() else {
+            return;
+        };
+        let cache = self.clone();
+        let ts = ts.clone();
+        let task = runtime.spawn(async move {
+            let _guard = guard;
+            let _ = cache.fetch(&ts, reason).await;
+        });
+        *self.background.lock().unwrap() = Some(task.abort_handle());
+    }
+
+    /// Aborts the refresh spawned in the background (if any), releasing the cache and the token source it holds.
+    pub(crate) fn abort_background(&self) {
+        if let Some(task) = self.background.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
+/// CacheKey
+///
+/// The key of the contextual tokens in the cache, composed of several dimensions of the request (e.g its host,
+/// audience and scope), computed by the [cache_key](crate::AuthorizationHeaderMiddlewareBuilder::cache_key) option.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::CacheKey;
+///
+///  let key = CacheKey::new().part("api.example.com").part("orders:read");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CacheKey(Vec<String>);
+
+impl CacheKey {
+    /// Creates an empty key, i.e the single key of the cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a dimension to the key.
+    pub fn part(mut self, part: impl Into<String>) -> Self {
+        self.0.push(part.into());
+        self
+    }
+
+    /// Returns the key of the entry in a [TokenCache], under the given namespace.
+    pub(crate) fn store_key(&self, namespace: &str) -> String {
+        self.0.iter().fold(namespace.to_string(), |key, part| {
+            format!("{key}:{}", part.replace('%', "%25").replace(':', "%3A"))
+        })
+    }
+}
+
+/// Computes the cache key of a request.
+pub(crate) type CacheKeyFn = Arc<dyn Fn(&Request) -> CacheKey + Send + Sync>;
+
+/// The token caches of a contextual source, one per [CacheKey], each with its own expiry and single fetch.
+pub(crate) struct KeyedCache {
+    key: CacheKeyFn,
+    strategy: CacheStrategy,
+    clock: Arc<dyn Clock>,
+    fetch_limit: Option<Arc<Semaphore>>,
+    // The shared storage of the tokens and its namespace, if any
+    store: Option<(Arc<dyn TokenCache>, String)>,
+    jitter: Option<Arc<Jitter>>,
+    failure_policy: RefreshFailurePolicy,
+    latency: Option<Arc<LatencyWindow>>,
+    counters: Arc<CacheCounters>,
+    auto_ttl_from_jwt: bool,
+    entries: Mutex<HashMap<CacheKey, Arc<Cache>>>,
+    // Set once cleared, so that the stored tokens of the keys are replaced rather than loaded again
+    bypass_store: AtomicBool,
+}
+
+impl KeyedCache {
+    pub(crate) fn new(
+        key: CacheKeyFn,
+        strategy: CacheStrategy,
+        clock: Arc<dyn Clock>,
+        fetch_limit: Option<Arc<Semaphore>>,
+    ) -> Self {
+        Self {
+            key,
+            strategy,
+            clock,
+            fetch_limit,
+            store: None,
+            jitter: None,
+            failure_policy: RefreshFailurePolicy::Retain,
+            latency: None,
+            counters: Arc::default(),
+            auto_ttl_from_jwt: false,
+            entries: Mutex::new(HashMap::new()),
+            bypass_store: AtomicBool::new(false),
+        }
+    }
+
+    /// Goes through the given storage, under the given namespace, on a miss (see [TokenCache]).
+    pub(crate) fn with_store(mut self, store: Arc<dyn TokenCache>, namespace: String) -> Self {
+        self.store = Some((store, namespace));
+        self
+    }
+
+    /// Makes the fetched tokens of all the keys expire earlier than the TTL, per the given jitter.
+    pub(crate) fn with_jitter(mut self, jitter: Option<Arc<Jitter>>) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets what to do with the expired tokens of all the keys when their refresh fails.
+    pub(crate) fn with_failure_policy(mut self, failure_policy: RefreshFailurePolicy) -> Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Records the durations of the fetches of all the keys in the given window (if any).
+    pub(crate) fn with_latency(mut self, latency: Option<Arc<LatencyWindow>>) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Counts the hits, misses and refreshes of all the keys in the given counters.
+    pub(crate) fn with_counters(mut self, counters: Arc<CacheCounters>) -> Self {
+        self.counters = counters;
+        self
+    }
+
+    /// Makes the fetched JWT tokens of all the keys expire per their `exp` claim rather than the TTL, when enabled.
+    pub(crate) fn with_auto_ttl_from_jwt(mut self, auto_ttl_from_jwt: bool) -> Self {
+        self.auto_ttl_from_jwt = auto_ttl_from_jwt;
+        self
+    }
+
+    /// Returns the strategy of the caches of the keys.
+    pub(crate) fn strategy(&self) -> CacheStrategy {
+        self.strategy
+    }
+
+    /// Returns what is done with the expired tokens when their refresh fails.
+    pub(crate) fn failure_policy(&self) -> RefreshFailurePolicy {
+        self.failure_policy
+    }
+
+    /// Returns the cache of the key of the request, created on first use.
+    ///
+    /// Creating an entry evicts the expired ones (or without a token) which no request is using, so that the entries
+    /// of the keys no longer used do not pile up.
+    pub(crate) fn get(&self, req: &Request) -> Arc<Cache> {
+        let key = (self.key)(req);
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(cache) = entries.get(&key) {
+            return cache.clone();
+        }
+        entries.retain(|_, cache| Arc::strong_count(cache) > 1 || cache.is_fresh());
+        let cache = Cache::new(self.strategy, self.clock.clone(), self.fetch_limit.clone())
+            .with_jitter(self.jitter.clone())
+            .with_failure_policy(self.failure_policy)
+            .with_latency(self.latency.clone())
+            .with_counters(self.counters.clone())
+            .with_auto_ttl_from_jwt(self.auto_ttl_from_jwt);
+        let cache = match &self.store {
+            Some((store, namespace)) => cache.with_store(store.clone(), key.store_key(namespace)),
+            None => cache,
+        };
+        // Until it saves a token of its own, the cache of a key does not load the one from before the clear
+        cache
+            .bypass_store
+            .store(self.bypass_store.load(Ordering::Relaxed), Ordering::Relaxed);
+        let cache = Arc::new(cache);
+        entries.insert(key, cache.clone());
+        cache
+    }
+
+    /// Returns the number of keys with an entry.
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Drops the cached tokens of all the keys, so that the next ones are fetched from the token source.
+    pub(crate) fn clear(&self) {
+        self.bypass_store.store(true, Ordering::Relaxed);
+        self.entries.lock().unwrap().clear();
+    }
+}
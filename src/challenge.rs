@@ -0,0 +1,52 @@
+//! Parsing of the challenges of the servers (e.g `WWW-Authenticate: Digest realm="...", nonce="..."`).
+
+use std::collections::HashMap;
+
+/// Parses a challenge into its scheme and parameters (with lowercase names).
+pub(crate) fn parse(challenge: &str) -> Option<(&str, HashMap<String, String>)> {
+    let (scheme, params) = challenge.trim().split_once(' ')?;
+    Some((scheme, parse_params(params)?))
+}
+
+/// Returns the realm of a challenge, whatever its scheme.
+pub(crate) fn realm(challenge: &str) -> Option<String> {
+    parse(challenge)?.1.remove("realm")
+}
+
+/// Parses the comma separated `name=value` parameters of a challenge, values being possibly quoted.
+fn parse_params(params: &str) -> Option<HashMap<String, String>> {
+    let mut parsed = HashMap::new();
+    let mut rest = params.trim();
+    while !rest.is_empty() {
+        let (name, after) = rest.split_once('=')?;
+        let (value, after) = match after.trim_start().strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"')?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => after.split_once(',').unwrap_or((after, "")),
+        };
+        parsed.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        rest = after.trim_start().trim_start_matches(',').trim_start();
+    }
+    Some(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, realm};
+
+    #[test]
+    fn test_realm() {
+        // Given - challenges of several schemes
+        // Then - their realm is extracted, if any
+        assert_eq!(realm(r#"Basic realm="admin", charset="UTF-8""#).as_deref(), Some("admin"));
+        assert_eq!(realm(r#"Digest nonce="n", Realm=users"#).as_deref(), Some("users"));
+        assert_eq!(realm("Bearer"), None);
+        assert_eq!(realm(r#"Bearer error="invalid_token""#), None);
+
+        // Then - the scheme and parameters are parsed
+        let (scheme, params) = parse(r#"Digest realm="r", nonce="n""#).unwrap();
+        assert_eq!((scheme, params["nonce"].as_str()), ("Digest", "n"));
+    }
+}
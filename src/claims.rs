@@ -0,0 +1,83 @@
+//! The expiry of JWT tokens, read from their `exp` claim with the `claims` feature (see
+//! [auto_ttl_from_jwt](crate::AuthorizationHeaderMiddlewareBuilder::auto_ttl_from_jwt)).
+//!
+//! Without the feature, no token has an expiry.
+
+use std::time::Duration;
+
+/// Returns in how long the given token expires per its `exp` claim (zero once expired), none when it is not a JWT
+/// or has no valid `exp` claim.
+///
+/// The signature is not verified: the claim is only trusted to schedule the refreshes of the token.
+#[cfg(feature = "claims")]
+pub(crate) fn expires_in(token: &str) -> Option<Duration> {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let mut parts = token.split('.');
+    let (Some(_header), Some(payload), Some(_signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&payload).ok()?;
+    // A NumericDate, which may have a fractional part
+    let exp = Duration::try_from_secs_f64(claims.get("exp")?.as_f64()?).ok()?;
+    let expiry = UNIX_EPOCH.checked_add(exp)?;
+    Some(expiry.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+#[cfg(not(feature = "claims"))]
+pub(crate) fn expires_in(_token: &str) -> Option<Duration> {
+    None
+}
+
+#[cfg(all(test, feature = "claims"))]
+mod tests {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::expires_in;
+
+    fn jwt(claims: &str) -> String {
+        format!(
+            "{}.{}.signature",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256"}"#),
+            URL_SAFE_NO_PAD.encode(claims)
+        )
+    }
+
+    #[test]
+    fn test_expires_in() {
+        // Given - a JWT expiring in 5 minutes
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let token = jwt(&format!(r#"{{"sub":"me","exp":{}}}"#, now + 300));
+
+        // Then - its expiry is read from its claims
+        let remaining = expires_in(&token).unwrap();
+        assert!(
+            remaining <= Duration::from_secs(300) && remaining > Duration::from_secs(290),
+            "{remaining:?}"
+        );
+
+        // Given - an expired JWT
+        // Then - it expires right away
+        assert_eq!(expires_in(&jwt(r#"{"exp":1000.5}"#)), Some(Duration::ZERO));
+
+        // Given - tokens which are not JWTs, or without a valid exp claim
+        // Then - they have no expiry
+        for token in [
+            "my-token".to_string(),
+            "a.b.c".to_string(),
+            jwt(r#"{"sub":"me"}"#),
+            jwt(r#"{"exp":"tomorrow"}"#),
+            jwt(r#"{"exp":-1}"#),
+            format!("{}.extra", jwt(r#"{"exp":1000}"#)),
+        ] {
+            assert_eq!(expires_in(&token), None, "{token}");
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt::Debug;
+use std::time::Instant;
+
+#[cfg(any(test, feature = "testing"))]
+use std::sync::Mutex;
+#[cfg(any(test, feature = "testing"))]
+use std::time::Duration;
+
+/// Clock
+///
+/// Abstracts how the current time is read, so that time dependent behaviors can be tested
+/// deterministically, advancing the time without sleeping.
+pub trait Clock: Send + Sync + Debug {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+}
+
+/// SystemClock
+///
+/// The default [Clock], reading the system monotonic time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// TestClock
+///
+/// A [Clock] only moving forward when told to.
+///
+/// Available with the `testing` feature.
+///
+/// # How to use
+///
+/// ```rust
+///  # #[cfg(feature = "testing")]
+///  # {
+///  use reqwest_auth::{Clock, TestClock};
+///  use std::time::Duration;
+///
+///  let clock = TestClock::new();
+///  let start = clock.now();
+///  clock.advance(Duration::from_secs(60));
+///  assert_eq!(clock.now() - start, Duration::from_secs(60));
+///  # }
+/// ```
+#[cfg(any(test, feature = "testing"))]
+#[derive(Debug)]
+pub struct TestClock {
+    start: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl TestClock {
+    /// Creates a clock frozen at the current instant.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Moves the clock forward by the given duration.
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Clock, TestClock};
+
+    #[test]
+    fn test_test_clock() {
+        // Given - a test clock
+        let clock = TestClock::new();
+        let start = clock.now();
+
+        // When - not advancing it
+        // Then - the time is frozen
+        assert_eq!(clock.now(), start);
+
+        // When - advancing it
+        // Then - the time moved forward by the given duration
+        clock.advance(Duration::from_secs(30));
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(clock.now() - start, Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,215 @@
+use reqwest_middleware::reqwest::header::HeaderName;
+use std::sync::Arc;
+use token_source::TokenSource;
+
+/// AuthRequestConfig
+///
+/// Per request overrides of the middleware options.
+///
+/// Place it in the request extensions to customize how a single request is handled.
+/// Every option that is set takes precedence over the middleware (instance) defaults,
+/// while the options left unset fall back to them.
+/// Requests without this config are handled per the middleware defaults.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::AuthRequestConfig;
+///
+///  // Send this request with a Basic scheme, whatever the middleware scheme is
+///  let config = AuthRequestConfig::new().scheme("Basic");
+///
+///  // Do not authorize this request at all
+///  let config = AuthRequestConfig::new().skip(true);
+/// ```
+///
+/// Then attach it using `reqwest_middleware::RequestBuilder::with_extension(config)`.
+#[derive(Clone, Debug, Default)]
+pub struct AuthRequestConfig {
+    pub(crate) scheme: Option<Option<String>>,
+    pub(crate) skip: Option<bool>,
+    pub(crate) header_name: Option<HeaderName>,
+}
+
+impl AuthRequestConfig {
+    /// Creates a config with no override.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the scheme prefixing the token, used verbatim as the middleware one.
+    pub fn scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.scheme = Some(Some(scheme.into()));
+        self
+    }
+
+    /// Overrides the scheme so that the token is used as is.
+    pub fn no_scheme(mut self) -> Self {
+        self.scheme = Some(None);
+        self
+    }
+
+    /// Sets whether the request should be sent without authorization.
+    pub fn skip(mut self, skip: bool) -> Self {
+        self.skip = Some(skip);
+        self
+    }
+
+    /// Overrides the name of the header receiving the token.
+    pub fn header_name(mut self, header_name: HeaderName) -> Self {
+        self.header_name = Some(header_name);
+        self
+    }
+}
+
+/// SchemeOverride
+///
+/// A per request override of the scheme prefixing the token, the other options being the middleware (instance)
+/// defaults. This is a lighter alternative to [AuthRequestConfig::scheme], which takes precedence over it.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::SchemeOverride;
+///
+///  // Send this request with a Basic scheme, whatever the middleware scheme is
+///  let scheme = SchemeOverride("Basic".to_string());
+/// ```
+///
+/// Then attach it using `reqwest_middleware::RequestBuilder::with_extension(scheme)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemeOverride(pub String);
+
+/// HostAuth
+///
+/// The authorization of the requests to a given host, registered with
+/// [host_auth](crate::AuthorizationHeaderMiddlewareBuilder::host_auth), or carrying a given tag, registered with
+/// [tag_auth](crate::AuthorizationHeaderMiddlewareBuilder::tag_auth).
+///
+/// Requests to the host (or with the tag) get their token from this config's token source, instead of the middleware
+/// one. The header name and scheme that are set take precedence over the middleware defaults, while the ones left
+/// unset fall back to them. A per request [AuthRequestConfig] still takes precedence over both.
+///
+/// # How to use
+///
+/// ```rust
+///  # #[derive(Debug)]
+///  # struct MyTokenSource;
+///  # #[async_trait::async_trait]
+///  # impl token_source::TokenSource for MyTokenSource {
+///  #   async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///  #     Ok("my-token".to_string())
+///  #   }
+///  # }
+///  use reqwest::header::HeaderName;
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, HostAuth};
+///  use std::sync::Arc;
+///
+///  // Requests to api.github.com get a "Token ..." header, the other ones a "Bearer ..." one
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(MyTokenSource))
+///    .scheme("Bearer")
+///    .host_auth("api.github.com", HostAuth::new(Arc::new(MyTokenSource)).scheme("Token"))
+///    .host_auth(
+///      "api.example.com",
+///      HostAuth::new(Arc::new(MyTokenSource)).no_scheme().header_name(HeaderName::from_static("x-api-key")),
+///    )
+///    .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct HostAuth {
+    pub(crate) source: Arc<dyn TokenSource>,
+    pub(crate) scheme: Option<Option<String>>,
+    pub(crate) header_name: Option<HeaderName>,
+    pub(crate) label: Option<String>,
+}
+
+impl HostAuth {
+    /// Creates a config providing the tokens from the given token source, with the middleware header name and scheme.
+    pub fn new(ts: Arc<dyn TokenSource>) -> Self {
+        Self {
+            source: ts,
+            scheme: None,
+            header_name: None,
+            label: None,
+        }
+    }
+
+    /// Sets the label naming this config in the [source logs](crate::AuthorizationHeaderMiddlewareBuilder::log_source),
+    /// instead of its tag position or host. Labels are logged as is, so they must not embed any credential.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Overrides the scheme prefixing the token, used verbatim as the middleware one.
+    pub fn scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.scheme = Some(Some(scheme.into()));
+        self
+    }
+
+    /// Overrides the scheme so that the token is used as is.
+    pub fn no_scheme(mut self) -> Self {
+        self.scheme = Some(None);
+        self
+    }
+
+    /// Overrides the name of the header receiving the token.
+    pub fn header_name(mut self, header_name: HeaderName) -> Self {
+        self.header_name = Some(header_name);
+        self
+    }
+}
+
+/// HeaderAuth
+///
+/// An additional header, registered with [header_auth](crate::AuthorizationHeaderMiddlewareBuilder::header_auth),
+/// set along the main one with a token from its own source and scheme, e.g while an API migrates from API keys to
+/// bearer tokens and accepts both.
+///
+/// Its tokens are cached independently of the middleware ones, per the same
+/// [cache strategy](crate::AuthorizationHeaderMiddlewareBuilder::cache_strategy) (if any).
+///
+/// # How to use
+///
+/// ```rust
+///  # #[derive(Debug)]
+///  # struct MyTokenSource;
+///  # #[async_trait::async_trait]
+///  # impl token_source::TokenSource for MyTokenSource {
+///  #   async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///  #     Ok("my-token".to_string())
+///  #   }
+///  # }
+///  use reqwest::header::HeaderName;
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, HeaderAuth};
+///  use std::sync::Arc;
+///
+///  // Requests get both an "x-api-key: <key>" and an "authorization: Bearer <token>" header
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(MyTokenSource))
+///    .scheme("Bearer")
+///    .header_auth(HeaderAuth::new(HeaderName::from_static("x-api-key"), Arc::new(MyTokenSource)))
+///    .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct HeaderAuth {
+    pub(crate) header_name: HeaderName,
+    pub(crate) source: Arc<dyn TokenSource>,
+    pub(crate) scheme: Option<String>,
+}
+
+impl HeaderAuth {
+    /// Creates a header receiving the tokens of the given token source as is (without scheme).
+    pub fn new(header_name: HeaderName, ts: Arc<dyn TokenSource>) -> Self {
+        Self {
+            header_name,
+            source: ts,
+            scheme: None,
+        }
+    }
+
+    /// Sets the scheme prefixing the token, used verbatim as the middleware one.
+    pub fn scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.scheme = Some(scheme.into());
+        self
+    }
+}
@@ -0,0 +1,119 @@
+use reqwest_middleware::reqwest::Method;
+use reqwest_middleware::reqwest::Url;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::challenge;
+use crate::nonce::Stamp;
+
+/// TokenSourceContext
+///
+/// An arbitrary value placed in the request extensions, forwarded to the [ContextualTokenSource]
+/// of the middleware when authorizing this request.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::TokenSourceContext;
+///
+///  // Any type can be used as context, as long as it is thread safe
+///  struct Audience(&'static str);
+///
+///  let ctx = TokenSourceContext::new(Audience("https://api.example.com"));
+/// ```
+///
+/// Then attach it using `reqwest_middleware::RequestBuilder::with_extension(ctx)`.
+#[derive(Clone)]
+pub struct TokenSourceContext(Arc<dyn Any + Send + Sync>);
+
+impl TokenSourceContext {
+    /// Wraps the given value as a token source context.
+    pub fn new<T: Any + Send + Sync>(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+
+    /// Returns the context value if it is of the given type.
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref::<T>()
+    }
+}
+
+impl From<Arc<dyn Any + Send + Sync>> for TokenSourceContext {
+    fn from(value: Arc<dyn Any + Send + Sync>) -> Self {
+        Self(value)
+    }
+}
+
+/// TokenContext
+///
+/// What a [ContextualTokenSource] knows about the request being authorized.
+pub struct TokenContext<'a> {
+    pub(crate) method: &'a Method,
+    pub(crate) url: &'a Url,
+    pub(crate) value: Option<&'a TokenSourceContext>,
+    pub(crate) challenge: Option<&'a str>,
+    pub(crate) stamp: Option<&'a Stamp>,
+}
+
+impl TokenContext<'_> {
+    /// Returns the method of the request.
+    pub fn method(&self) -> &Method {
+        self.method
+    }
+
+    /// Returns the url of the request.
+    pub fn url(&self) -> &Url {
+        self.url
+    }
+
+    /// Returns the [TokenSourceContext] value of the request, if any and of the given type.
+    pub fn value<T: Any>(&self) -> Option<&T> {
+        self.value.and_then(|ctx| ctx.get::<T>())
+    }
+
+    /// Returns the challenge the server answered the unauthorized first attempt of the request with, if any.
+    ///
+    /// See [challenge_header](crate::AuthorizationHeaderMiddlewareBuilder::challenge_header).
+    pub fn challenge(&self) -> Option<&str> {
+        self.challenge
+    }
+
+    /// Returns the realm of the [challenge](Self::challenge) (e.g `Basic realm="admin"`), if any.
+    ///
+    /// This lets the token source select the credentials of the realm, for the servers with several ones.
+    pub fn realm(&self) -> Option<String> {
+        self.challenge.and_then(challenge::realm)
+    }
+
+    /// Returns the nonce sent along the token, if any.
+    ///
+    /// See [anti_replay](crate::AuthorizationHeaderMiddlewareBuilder::anti_replay).
+    pub fn nonce(&self) -> Option<&str> {
+        self.stamp.map(|stamp| stamp.nonce.as_str())
+    }
+
+    /// Returns the timestamp (unix time, in seconds) sent along the token, if any.
+    ///
+    /// See [anti_replay](crate::AuthorizationHeaderMiddlewareBuilder::anti_replay).
+    pub fn timestamp(&self) -> Option<&str> {
+        self.stamp.map(|stamp| stamp.timestamp.as_str())
+    }
+}
+
+/// ContextualTokenSource
+///
+/// A token source providing tokens based on the request being authorized,
+/// including the [TokenSourceContext] found in its extensions (if any).
+///
+/// This enables per request token customization (e.g scope, audience, tenant) without requiring
+/// a new trait per use case.
+#[async_trait::async_trait]
+pub trait ContextualTokenSource: Send + Sync + Debug {
+    /// Returns a valid token for the given context,
+    /// or none if the request should be sent without authorization.
+    async fn token_with(
+        &self,
+        ctx: &TokenContext<'_>,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+use std::time::Instant;
+
+/// Deadline
+///
+/// The instant by which a request should be done, placed in its extensions (e.g by a parent context).
+///
+/// The token fetch of the request is bounded by the remaining time, taking precedence over the
+/// [token timeout](crate::AuthorizationHeaderMiddlewareBuilder::token_timeout) of the middleware.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::Deadline;
+///  use std::time::Duration;
+///
+///  let deadline = Deadline::after(Duration::from_secs(5));
+/// ```
+///
+/// Then attach it using `reqwest_middleware::RequestBuilder::with_extension(deadline)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// Creates a deadline at the given instant.
+    pub fn new(instant: Instant) -> Self {
+        Self(instant)
+    }
+
+    /// Creates a deadline once the given duration elapsed from now.
+    pub fn after(duration: Duration) -> Self {
+        Self(Instant::now() + duration)
+    }
+
+    /// Returns the instant of the deadline.
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Returns the time left until the deadline, zero if it passed.
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+}
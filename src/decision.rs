@@ -0,0 +1,48 @@
+use reqwest_middleware::reqwest::Request;
+use std::fmt::Debug;
+
+/// What to do with a request, as decided by a [PreSendHook].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Decision {
+    /// Authorize the request.
+    #[default]
+    Proceed,
+    /// Send the request without authorization.
+    Skip,
+    /// Fail the request with an [AuthError::Aborted](crate::AuthError::Aborted) error.
+    Abort,
+}
+
+/// PreSendHook
+///
+/// Decides what to do with each request about to be authorized, set with
+/// [pre_send_hook](crate::AuthorizationHeaderMiddlewareBuilder::pre_send_hook).
+///
+/// Unlike the filters of the builder, the decision can be asynchronous, e.g looking up a feature flag.
+/// A failing hook fails the request with an [AuthError::PreSendHook](crate::AuthError::PreSendHook) error.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest::Request;
+///  use reqwest_auth::{Decision, PreSendHook};
+///
+///  // Only authorize the requests to internal hosts, and refuse to send the others
+///  #[derive(Debug)]
+///  struct InternalOnly;
+///
+///  #[async_trait::async_trait]
+///  impl PreSendHook for InternalOnly {
+///    async fn decide(&self, req: &Request) -> Result<Decision, Box<dyn std::error::Error + Send + Sync>> {
+///      match req.url().host_str() {
+///        Some(host) if host.ends_with(".internal") => Ok(Decision::Proceed),
+///        _ => Ok(Decision::Abort),
+///      }
+///    }
+///  }
+/// ```
+#[async_trait::async_trait]
+pub trait PreSendHook: Send + Sync + Debug {
+    /// Returns what to do with the request, before any token is fetched for it.
+    async fn decide(&self, req: &Request) -> Result<Decision, Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,235 @@
+use reqwest_middleware::reqwest::header::{InvalidHeaderName, InvalidHeaderValue};
+use std::time::Duration;
+
+/// AuthError
+///
+/// The errors raised by the middleware.
+///
+/// They are wrapped in a [reqwest_middleware::Error::Middleware], and can be retrieved through
+/// `anyhow::Error::downcast_ref::<AuthError>()`.
+/// The underlying errors (e.g the token source one) are preserved and available through
+/// [Error::source](std::error::Error::source).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum AuthError {
+    /// The token source failed to provide a token.
+    #[error("Token source error: {0}")]
+    TokenSource(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The token (or its scheme) is not a valid header value: it has a control character (e.g a trailing newline)
+    /// other than the tab.
+    ///
+    /// Other characters, within or beyond ASCII, are valid: see
+    /// [latin1_tokens](crate::AuthorizationHeaderMiddlewareBuilder::latin1_tokens) for how they are encoded.
+    #[error("Invalid auth token value: {0}")]
+    InvalidHeaderValue(#[from] InvalidHeaderValue),
+    /// The token (or its scheme) has a character beyond `U+00FF`, which has no single byte encoding in the
+    /// [latin1_tokens](crate::AuthorizationHeaderMiddlewareBuilder::latin1_tokens) mode.
+    #[error("Auth token value is not ISO-8859-1: invalid character at position {position}")]
+    NonLatin1Token {
+        /// The position of the first invalid character (in characters, not bytes) in the header value.
+        position: usize,
+    },
+    /// The header name computed for the request is not a valid one.
+    #[error("Invalid auth header name: {0}")]
+    InvalidHeaderName(#[from] InvalidHeaderName),
+    /// A header configured to carry credentials is a connection specific one (e.g `Connection`), which HTTP/2
+    /// forbids: the connection would drop it, or reject the request, once negotiated.
+    #[error("Invalid auth header name: {name} is a connection specific header, forbidden by HTTP/2")]
+    ConnectionHeaderName {
+        /// The name of the header.
+        name: String,
+    },
+    /// The request was about to send credentials over a plaintext (non https) connection.
+    #[error("Refusing to send credentials to {host} over {scheme}, https is required")]
+    InsecureTransport {
+        /// The scheme of the request url.
+        scheme: String,
+        /// The host of the request url.
+        host: String,
+    },
+    /// The token is longer than the [maximum length](crate::AuthorizationHeaderMiddlewareBuilder::max_token_len).
+    #[error("Auth token too long: {len} bytes, the maximum is {max}")]
+    TokenTooLong {
+        /// The length of the token, in bytes.
+        len: usize,
+        /// The maximum length, in bytes.
+        max: usize,
+    },
+    /// The token failed the [validation](crate::AuthorizationHeaderMiddlewareBuilder::validate_token) twice: once
+    /// fetched, and once refetched.
+    #[error("Auth token failed the validation: {0}")]
+    InvalidToken(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The token source did not provide a token in time, per the request [Deadline](crate::Deadline)
+    /// or the [token timeout](crate::AuthorizationHeaderMiddlewareBuilder::token_timeout).
+    #[error("Token source did not provide a token within {0:?}")]
+    TokenTimeout(Duration),
+    /// The middleware was built with [try_build](crate::AuthorizationHeaderMiddlewareBuilder::try_build) while its
+    /// token sources [must be verified](crate::AuthorizationHeaderMiddlewareBuilder::must_verify), which only
+    /// [build_and_verify](crate::AuthorizationHeaderMiddlewareBuilder::build_and_verify) does.
+    #[error("The token sources must be verified: build the middleware with build_and_verify")]
+    VerificationRequired,
+    /// The OS secure random generator failed to provide the nonce of the
+    /// [anti replay headers](crate::AuthorizationHeaderMiddlewareBuilder::anti_replay).
+    #[error("The nonce could not be generated: {0}")]
+    Randomness(#[source] std::io::Error),
+    /// The body of a request which may need to be retried cannot be cloned, per the
+    /// [retry body policy](crate::AuthorizationHeaderMiddlewareBuilder::retry_body_policy).
+    #[error("The request body cannot be cloned for retries")]
+    UncloneableBody,
+    /// The body of a request could not be buffered for retries.
+    #[error("The request body could not be read: {0}")]
+    BodyRead(#[source] reqwest_middleware::reqwest::Error),
+    /// The credentials are disabled by the [kill switch](crate::AuthorizationHeaderMiddlewareBuilder::kill_switch).
+    #[error("Credentials are disabled by the kill switch")]
+    KillSwitch,
+    /// The [pre-send hook](crate::AuthorizationHeaderMiddlewareBuilder::pre_send_hook) aborted the request.
+    #[error("The request was aborted by the pre-send hook")]
+    Aborted,
+    /// The [pre-send hook](crate::AuthorizationHeaderMiddlewareBuilder::pre_send_hook) failed to decide.
+    #[error("Pre-send hook error: {0}")]
+    PreSendHook(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The [unauthorized hook](crate::AuthorizationHeaderMiddlewareBuilder::on_unauthorized) failed to recover.
+    #[error("Unauthorized hook error: {0}")]
+    UnauthorizedHook(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl AuthError {
+    /// Returns the original token source error, if that is what failed.
+    ///
+    /// This allows downcasting it to the concrete error type of your token source.
+    pub fn token_source_error(&self) -> Option<&(dyn std::error::Error + Send + Sync + 'static)> {
+        match self {
+            Self::TokenSource(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Converts into the original token source error, if that is what failed.
+    pub fn into_token_source_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        match self {
+            Self::TokenSource(e) => Ok(e),
+            e => Err(e),
+        }
+    }
+}
+
+/// ErrorVerbosity
+///
+/// How much of the token source errors is part of the errors of the middleware, set with
+/// [error_verbosity](crate::AuthorizationHeaderMiddlewareBuilder::error_verbosity).
+///
+/// The errors of the middleware never contain the tokens, but the token source errors are its own: they may detail
+/// internals (e.g the identity provider urls and responses).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorVerbosity {
+    /// The original token source errors are preserved, with their whole chain.
+    #[default]
+    Full,
+    /// The token source errors are replaced by a [RedactedError]: the [AuthError::TokenSource] kind remains,
+    /// without any detail.
+    Redacted,
+}
+
+impl ErrorVerbosity {
+    /// Redacts the token source error (if that is what failed) of the middleware error, per the verbosity.
+    pub(crate) fn apply(self, e: reqwest_middleware::Error) -> reqwest_middleware::Error {
+        match (self, &e) {
+            (Self::Redacted, reqwest_middleware::Error::Middleware(err))
+                if matches!(err.downcast_ref::<AuthError>(), Some(AuthError::TokenSource(_))) =>
+            {
+                AuthError::TokenSource(Box::new(RedactedError)).into()
+            }
+            _ => e,
+        }
+    }
+}
+
+/// RedactedError
+///
+/// Stands for a token source error, with the [Redacted](ErrorVerbosity::Redacted) error verbosity.
+#[derive(Debug, thiserror::Error)]
+#[error("details redacted")]
+pub struct RedactedError;
+
+impl From<AuthError> for reqwest_middleware::Error {
+    fn from(e: AuthError) -> Self {
+        reqwest_middleware::Error::Middleware(anyhow::Error::new(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::{AuthError, ErrorVerbosity, RedactedError};
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("provider is down")]
+    struct ProviderError;
+
+    #[test]
+    fn test_source_chain() {
+        // Given - a token source error
+        let err = AuthError::TokenSource(Box::new(ProviderError));
+
+        // When - wrapping it as a middleware error
+        let reqwest_middleware::Error::Middleware(err) = reqwest_middleware::Error::from(err) else {
+            panic!("Expected a middleware error");
+        };
+
+        // Then - the original error is preserved in the source chain
+        assert_eq!(err.to_string(), "Token source error: provider is down");
+        let auth_err = err.downcast_ref::<AuthError>().unwrap();
+        assert!(auth_err.source().unwrap().downcast_ref::<ProviderError>().is_some());
+    }
+
+    #[test]
+    fn test_token_source_error() {
+        // Given - a token source error, and another one
+        let err = AuthError::TokenSource(Box::new(ProviderError));
+        let other = AuthError::InsecureTransport {
+            scheme: "http".to_string(),
+            host: "example.com".to_string(),
+        };
+
+        // Then - the original error can be downcast to its concrete type
+        assert!(err
+            .token_source_error()
+            .unwrap()
+            .downcast_ref::<ProviderError>()
+            .is_some());
+        assert!(other.token_source_error().is_none());
+        assert!(err
+            .into_token_source_error()
+            .unwrap()
+            .downcast::<ProviderError>()
+            .is_ok());
+        assert!(other.into_token_source_error().is_err());
+    }
+
+    #[test]
+    fn test_error_verbosity() {
+        let err = || reqwest_middleware::Error::from(AuthError::TokenSource(Box::new(ProviderError)));
+
+        // Given - the full verbosity
+        // Then - the token source error is preserved
+        assert_eq!(
+            ErrorVerbosity::Full.apply(err()).to_string(),
+            "Token source error: provider is down"
+        );
+
+        // Given - the redacted verbosity
+        // Then - the token source error is replaced, keeping its kind
+        let reqwest_middleware::Error::Middleware(redacted) = ErrorVerbosity::Redacted.apply(err()) else {
+            panic!("Expected a middleware error");
+        };
+        assert_eq!(redacted.to_string(), "Token source error: details redacted");
+        assert!(!format!("{redacted:?}").contains("provider"));
+        let auth_err = redacted.downcast_ref::<AuthError>().unwrap();
+        assert!(auth_err.token_source_error().unwrap().is::<RedactedError>());
+
+        // Then - the other errors are preserved
+        let other = ErrorVerbosity::Redacted.apply(AuthError::TokenTooLong { len: 8, max: 4 }.into());
+        assert_eq!(other.to_string(), "Auth token too long: 8 bytes, the maximum is 4");
+    }
+}
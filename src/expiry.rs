@@ -0,0 +1,38 @@
+use std::time::Duration;
+use std::time::Instant;
+
+/// TokenExpiry
+///
+/// When the token a request was authorized with expires, placed in the extensions of its response
+/// with the [token_expiry](crate::AuthorizationHeaderMiddlewareBuilder::token_expiry) option.
+///
+/// The expiry is the end of the TTL of the cached token, as read from the
+/// [clock](crate::AuthorizationHeaderMiddlewareBuilder::clock) of the middleware.
+///
+/// # How to use
+///
+/// ```rust,no_run
+///  # async fn run(client: reqwest_middleware::ClientWithMiddleware) -> reqwest_middleware::Result<()> {
+///  use reqwest_auth::TokenExpiry;
+///
+///  let res = client.get("https://example.com").send().await?;
+///  if let Some(expiry) = res.extensions().get::<TokenExpiry>() {
+///    println!("Session expires in {:?}", expiry.remaining());
+///  }
+///  # Ok(())
+///  # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenExpiry(pub(crate) Instant);
+
+impl TokenExpiry {
+    /// Returns the instant the token expires at.
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Returns the time left until the token expires, zero if it already did.
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+}
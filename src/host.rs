@@ -0,0 +1,105 @@
+use reqwest_middleware::reqwest::Request;
+use std::net::Ipv6Addr;
+use std::sync::Arc;
+use url::Host;
+
+/// Extracts the effective host of a request, used for host based decisions.
+pub(crate) type HostExtractor = Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>;
+
+/// Returns the effective host of the request: the one returned by the extractor if any,
+/// the host of the request url otherwise.
+pub(crate) fn effective_host(req: &Request, extractor: Option<&HostExtractor>) -> Option<String> {
+    match extractor {
+        Some(extractor) => extractor(req),
+        None => req.url().host_str().map(str::to_string),
+    }
+}
+
+/// Returns whether the two hosts are the same, compared case insensitively as exact names.
+///
+/// IPv6 literals are compared as addresses, with or without brackets: `[::1]`, `::1` and `[0:0:0:0:0:0:0:1]`
+/// are the same host (the url of a request has bracketed ones).
+pub(crate) fn same_host(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b) || ipv6(a).is_some_and(|ip| ipv6(b) == Some(ip))
+}
+
+/// Parses an IPv6 literal, with or without brackets.
+fn ipv6(host: &str) -> Option<Ipv6Addr> {
+    let unbracketed = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+    unbracketed.parse().ok()
+}
+
+/// Returns whether the host is a loopback one.
+///
+/// This is a name based check: `localhost` (and its subdomains) as well as loopback IP addresses
+/// (e.g `127.0.0.1` or `::1`) are considered loopback, no DNS resolution is performed.
+pub(crate) fn is_loopback(host: &str) -> bool {
+    match Host::parse(host) {
+        Ok(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.');
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        Ok(Host::Ipv4(ip)) => ip.is_loopback(),
+        Ok(Host::Ipv6(ip)) => ip.is_loopback(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::{Method, Request, Url};
+    use std::sync::Arc;
+
+    use super::{effective_host, is_loopback, same_host, HostExtractor};
+
+    #[test]
+    fn test_is_loopback() {
+        for host in [
+            "localhost",
+            "LocalHost",
+            "api.localhost",
+            "127.0.0.1",
+            "127.1.2.3",
+            "[::1]",
+        ] {
+            assert!(is_loopback(host), "{host} should be loopback");
+        }
+        for host in ["example.com", "localhost.example.com", "10.0.0.1", "[::2]"] {
+            assert!(!is_loopback(host), "{host} should not be loopback");
+        }
+    }
+
+    #[test]
+    fn test_same_host() {
+        for (a, b) in [
+            ("example.com", "Example.COM"),
+            ("[::1]", "::1"),
+            ("[::1]", "[0:0:0:0:0:0:0:1]"),
+            ("[2001:db8::8a2e:370:7334]", "2001:0DB8:0000:0000:0000:8A2E:0370:7334"),
+        ] {
+            assert!(same_host(a, b), "{a} and {b} should be the same host");
+        }
+        for (a, b) in [
+            ("example.com", "api.example.com"),
+            ("[::1]", "[::2]"),
+            ("[::1]", "127.0.0.1"),
+        ] {
+            assert!(!same_host(a, b), "{a} and {b} should not be the same host");
+        }
+    }
+
+    #[test]
+    fn test_effective_host() {
+        let req = Request::new(Method::GET, Url::parse("https://api.example.com/path").unwrap());
+
+        // By default, the host of the url
+        assert_eq!(effective_host(&req, None).as_deref(), Some("api.example.com"));
+
+        // Otherwise, the one of the extractor
+        let extractor: HostExtractor = Arc::new(|_| Some("proxy.internal".to_string()));
+        assert_eq!(effective_host(&req, Some(&extractor)).as_deref(), Some("proxy.internal"));
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::Clock;
+
+/// FetchLatencies
+///
+/// The percentiles of the durations of the latest token fetches, returned by
+/// [fetch_latencies](crate::AuthorizationHeaderMiddleware::fetch_latencies) with the
+/// [fetch_latency_window](crate::AuthorizationHeaderMiddlewareBuilder::fetch_latency_window) option, e.g for the
+/// capacity planning of the identity provider.
+///
+/// The percentiles are exact over the window (nearest rank), covering the successful and failed fetches alike.
+///
+/// # How to use
+///
+/// ```rust
+///  # #[derive(Debug)]
+///  # struct MyTokenSource;
+///  # #[async_trait::async_trait]
+///  # impl token_source::TokenSource for MyTokenSource {
+///  #   async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///  #     Ok("my-token".to_string())
+///  #   }
+///  # }
+///  use reqwest_auth::AuthorizationHeaderMiddleware;
+///  use std::sync::Arc;
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(MyTokenSource))
+///    .fetch_latency_window(512)
+///    .build();
+///
+///  // Once tokens were fetched
+///  if let Some(latencies) = auth_middleware.fetch_latencies() {
+///    println!("Token fetches: p50 {:?}, p99 {:?}", latencies.p50(), latencies.p99());
+///  }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FetchLatencies {
+    pub(crate) p50: Duration,
+    pub(crate) p95: Duration,
+    pub(crate) p99: Duration,
+    pub(crate) samples: usize,
+}
+
+impl FetchLatencies {
+    /// Returns the median duration of the fetches.
+    pub fn p50(&self) -> Duration {
+        self.p50
+    }
+
+    /// Returns the duration under which 95% of the fetches completed.
+    pub fn p95(&self) -> Duration {
+        self.p95
+    }
+
+    /// Returns the duration under which 99% of the fetches completed.
+    pub fn p99(&self) -> Duration {
+        self.p99
+    }
+
+    /// Returns how many fetches the percentiles are computed from, at most the size of the window.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+}
+
+/// The durations of the latest token fetches, as read from the clock of the middleware.
+///
+/// Recording is constant time, the oldest duration making room for the new one once the window is full; the
+/// percentiles are selected (in linear time) when read.
+pub(crate) struct LatencyWindow {
+    clock: Arc<dyn Clock>,
+    size: usize,
+    samples: Mutex<VecDeque<Duration>>,
+}
+
+impl LatencyWindow {
+    pub(crate) fn new(size: usize, clock: Arc<dyn Clock>) -> Self {
+        let size = size.max(1);
+        Self {
+            clock,
+            size,
+            samples: Mutex::new(VecDeque::with_capacity(size)),
+        }
+    }
+
+    /// Records the duration of the given fetch, from its first poll (e.g once a permit of the fetch limit is
+    /// acquired), in the window (if any).
+    pub(crate) async fn measure<T>(window: Option<&Self>, fetch: impl Future<Output = T>) -> T {
+        let Some(window) = window else {
+            return fetch.await;
+        };
+        let start = window.clock.now();
+        let res = fetch.await;
+        window.record(window.clock.now().saturating_duration_since(start));
+        res
+    }
+
+    fn record(&self, duration: Duration) {
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() == self.size {
+            samples.pop_front();
+        }
+        samples.push_back(duration);
+    }
+
+    /// Returns the percentiles of the recorded durations, none before the first fetch.
+    pub(crate) fn percentiles(&self) -> Option<FetchLatencies> {
+        let mut samples: Vec<Duration> = self.samples.lock().unwrap().iter().copied().collect();
+        if samples.is_empty() {
+            return None;
+        }
+        let mut percentile = |percent: usize| {
+            // Nearest rank: the smallest duration with at least the given percentage of the durations up to it
+            let rank = (samples.len() * percent).div_ceil(100).max(1);
+            *samples.select_nth_unstable(rank - 1).1
+        };
+        Some(FetchLatencies {
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+            samples: samples.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::LatencyWindow;
+    use crate::SystemClock;
+
+    #[test]
+    fn test_percentiles() {
+        // Given - a window of 100 durations
+        let window = LatencyWindow::new(100, Arc::new(SystemClock));
+        assert!(window.percentiles().is_none());
+
+        // When - recording more durations than the window holds, in any order
+        for millis in (1..=150).rev() {
+            window.record(Duration::from_millis(millis));
+        }
+
+        // Then - the percentiles are the ones of the latest durations
+        let latencies = window.percentiles().unwrap();
+        assert_eq!(latencies.samples(), 100);
+        assert_eq!(latencies.p50(), Duration::from_millis(50));
+        assert_eq!(latencies.p95(), Duration::from_millis(95));
+        assert_eq!(latencies.p99(), Duration::from_millis(99));
+
+        // When - recording a single duration
+        let window = LatencyWindow::new(100, Arc::new(SystemClock));
+        window.record(Duration::from_millis(7));
+
+        // Then - it is all the percentiles
+        let latencies = window.percentiles().unwrap();
+        assert_eq!(
+            (latencies.p50(), latencies.p99()),
+            (Duration::from_millis(7), Duration::from_millis(7))
+        );
+    }
+}
@@ -0,0 +1,13 @@
+use std::future::Future;
+use tokio::sync::Semaphore;
+
+/// Runs the token fetch once a permit of the semaphore (if any) is acquired.
+///
+/// A closed semaphore no longer limits the fetches.
+pub(crate) async fn fetch<T>(semaphore: Option<&Semaphore>, fetch: impl Future<Output = T>) -> T {
+    let _permit = match semaphore {
+        Some(semaphore) => semaphore.acquire().await.ok(),
+        None => None,
+    };
+    fetch.await
+}
@@ -0,0 +1,139 @@
+use reqwest_middleware::reqwest::{Method, Request};
+use std::fmt::{Debug, Formatter};
+use std::ops::Not;
+use std::sync::Arc;
+
+use crate::host;
+
+/// RequestMatcher
+///
+/// A condition over the requests, set with [auth_when](crate::AuthorizationHeaderMiddlewareBuilder::auth_when) to
+/// only authorize the matching ones.
+///
+/// Matchers are combined with [and](Self::and), [or](Self::or) and `!` (the [Not] operator) into a single
+/// condition, evaluated left to right and short circuiting: `a.and(b)` does not evaluate `b` when `a` does not match.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest::Method;
+///  use reqwest_auth::RequestMatcher;
+///
+///  // The writes to the v2 API, except for its health endpoint
+///  let matcher = RequestMatcher::host("api.example.com")
+///    .and(RequestMatcher::path_prefix("/v2"))
+///    .and(RequestMatcher::method(Method::POST).or(RequestMatcher::method(Method::PUT)))
+///    .and(!RequestMatcher::path_prefix("/v2/health"));
+/// ```
+#[derive(Clone)]
+pub struct RequestMatcher(Arc<dyn Fn(&Request) -> bool + Send + Sync>);
+
+impl RequestMatcher {
+    /// Creates a matcher from the given predicate.
+    pub fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&Request) -> bool + Send + Sync + 'static,
+    {
+        Self(Arc::new(predicate))
+    }
+
+    /// Matches the requests whose url is to the given host, compared as
+    /// [allowed_hosts](crate::AuthorizationHeaderMiddlewareBuilder::allowed_hosts) do.
+    ///
+    /// Unlike the allowed hosts, the host of the url itself is matched, not the
+    /// [effective host](crate::AuthorizationHeaderMiddlewareBuilder::effective_host).
+    pub fn host(host: impl Into<String>) -> Self {
+        let host = host.into();
+        Self::new(move |req| {
+            req.url()
+                .host_str()
+                .is_some_and(|actual| host::same_host(actual, &host))
+        })
+    }
+
+    /// Matches the requests whose url path starts with the given one, on a segment boundary: `/v2` matches `/v2`
+    /// and `/v2/orders`, but not `/v20`.
+    pub fn path_prefix(prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        Self::new(move |req| {
+            req.url()
+                .path()
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/'))
+        })
+    }
+
+    /// Matches the requests with the given method.
+    pub fn method(method: Method) -> Self {
+        Self::new(move |req| req.method() == method)
+    }
+
+    /// Matches the requests matching both this matcher and the given one.
+    pub fn and(self, other: RequestMatcher) -> Self {
+        Self::new(move |req| self.matches(req) && other.matches(req))
+    }
+
+    /// Matches the requests matching this matcher, the given one, or both.
+    pub fn or(self, other: RequestMatcher) -> Self {
+        Self::new(move |req| self.matches(req) || other.matches(req))
+    }
+
+    /// Returns whether the given request matches.
+    pub fn matches(&self, req: &Request) -> bool {
+        (self.0)(req)
+    }
+}
+
+impl Not for RequestMatcher {
+    type Output = RequestMatcher;
+
+    /// Matches the requests not matching this matcher.
+    fn not(self) -> Self::Output {
+        Self::new(move |req| !self.matches(req))
+    }
+}
+
+impl Debug for RequestMatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestMatcher").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::{Method, Request, Url};
+
+    use super::RequestMatcher;
+
+    fn request(method: Method, url: &str) -> Request {
+        Request::new(method, Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_matchers() {
+        // Given - the POST requests to the v2 API, except for its health endpoint
+        let matcher = RequestMatcher::host("api.example.com")
+            .and(RequestMatcher::path_prefix("/v2"))
+            .and(RequestMatcher::method(Method::POST))
+            .and(!RequestMatcher::path_prefix("/v2/health"));
+
+        // When - matching requests
+        // Then - only the requests satisfying all the conditions match
+        for (method, url, expected) in [
+            (Method::POST, "https://API.example.com/v2", true),
+            (Method::POST, "https://api.example.com/v2/orders?id=1", true),
+            (Method::GET, "https://api.example.com/v2/orders", false),
+            (Method::POST, "https://api.example.com/v20/orders", false),
+            (Method::POST, "https://api.example.com/v2/health", false),
+            (Method::POST, "https://example.com/v2/orders", false),
+        ] {
+            assert_eq!(matcher.matches(&request(method, url)), expected, "{url}");
+        }
+
+        // Then - either side of an or matches
+        let matcher = RequestMatcher::method(Method::PUT).or(RequestMatcher::path_prefix("/admin/"));
+        assert!(matcher.matches(&request(Method::PUT, "https://example.com")));
+        assert!(matcher.matches(&request(Method::GET, "https://example.com/admin/users")));
+        assert!(!matcher.matches(&request(Method::GET, "https://example.com/users")));
+    }
+}
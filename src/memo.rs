@@ -0,0 +1,164 @@
+//! Memoization of the last header values, sparing their formatting and validation while the tokens do not change.
+//!
+//! A header value only depends on the scheme and the token, not on the request (method, host or path): consecutive
+//! requests with the same tokens reuse the same values, whatever they target. The values of the main header and of
+//! the additional ones (secondary headers and header auths) are memoized, not the per request ones (e.g anti replay).
+
+use reqwest_middleware::reqwest::header::{HeaderName, HeaderValue};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::cache::{peek, protect, TokenValue};
+use crate::AuthError;
+
+struct Entry {
+    scheme: Option<String>,
+    token: TokenValue,
+    value: HeaderValue,
+}
+
+impl Entry {
+    fn new(scheme: Option<&str>, token: &str, value: HeaderValue) -> Self {
+        Self {
+            scheme: scheme.map(str::to_string),
+            token: protect(token.to_string()),
+            value,
+        }
+    }
+
+    /// Returns the value if it was formatted from the given scheme and token.
+    fn value_for(&self, scheme: Option<&str>, token: &str) -> Option<HeaderValue> {
+        (self.scheme.as_deref() == scheme && peek(&self.token) == token).then(|| self.value.clone())
+    }
+}
+
+/// The last header value, along with the scheme and token it was formatted from.
+#[derive(Default)]
+pub(crate) struct LastValue(RwLock<Option<Entry>>);
+
+impl LastValue {
+    /// Returns the last header value if it was formatted from the same scheme and token, or formats a new one.
+    pub(crate) fn get_or_format(
+        &self,
+        scheme: Option<&str>,
+        token: &str,
+        format: impl FnOnce() -> Result<HeaderValue, AuthError>,
+    ) -> Result<HeaderValue, AuthError> {
+        if let Some(value) = self
+            .0
+            .read()
+            .unwrap()
+            .as_ref()
+            .and_then(|entry| entry.value_for(scheme, token))
+        {
+            return Ok(value);
+        }
+        let value = format()?;
+        *self.0.write().unwrap() = Some(Entry::new(scheme, token, value.clone()));
+        Ok(value)
+    }
+}
+
+/// The last values of the additional headers, per header name.
+#[derive(Default)]
+pub(crate) struct LastValues(RwLock<HashMap<HeaderName, Entry>>);
+
+impl LastValues {
+    /// Returns the last value of the header if it was formatted from the same scheme and token, or formats a new one.
+    pub(crate) fn get_or_format(
+        &self,
+        header_name: &HeaderName,
+        scheme: Option<&str>,
+        token: &str,
+        format: impl FnOnce() -> Result<HeaderValue, AuthError>,
+    ) -> Result<HeaderValue, AuthError> {
+        let last = self
+            .0
+            .read()
+            .unwrap()
+            .get(header_name)
+            .and_then(|entry| entry.value_for(scheme, token));
+        if let Some(value) = last {
+            return Ok(value);
+        }
+        let value = format()?;
+        self.0
+            .write()
+            .unwrap()
+            .insert(header_name.clone(), Entry::new(scheme, token, value.clone()));
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::header::{HeaderName, HeaderValue};
+    use std::cell::Cell;
+
+    use super::{LastValue, LastValues};
+    use crate::AuthError;
+
+    #[test]
+    fn test_last_value() {
+        let last = LastValue::default();
+        let formats = Cell::new(0);
+        let get = |scheme: Option<&str>, token: &str| {
+            last.get_or_format(scheme, token, || {
+                formats.set(formats.get() + 1);
+                let value = match scheme {
+                    Some(scheme) => format!("{scheme} {token}"),
+                    None => token.to_string(),
+                };
+                Ok::<_, AuthError>(HeaderValue::try_from(value)?)
+            })
+            .unwrap()
+        };
+
+        // When - formatting the same value twice
+        // Then - it is only formatted once
+        assert_eq!(get(Some("Bearer"), "token-1"), "Bearer token-1");
+        assert_eq!(get(Some("Bearer"), "token-1"), "Bearer token-1");
+        assert_eq!(formats.get(), 1);
+
+        // When - the token or the scheme changes
+        // Then - the value is formatted again
+        assert_eq!(get(Some("Bearer"), "token-2"), "Bearer token-2");
+        assert_eq!(get(Some("Basic"), "token-2"), "Basic token-2");
+        assert_eq!(get(None, "token-2"), "token-2");
+        assert_eq!(formats.get(), 4);
+
+        // Then - invalid values are not memoized
+        assert!(last
+            .get_or_format(None, "token\n", || Ok(HeaderValue::try_from("token\n")?))
+            .is_err());
+        assert_eq!(get(None, "token-2"), "token-2");
+        assert_eq!(formats.get(), 4);
+    }
+
+    #[test]
+    fn test_last_values() {
+        let last = LastValues::default();
+        let formats = Cell::new(0);
+        let get = |header_name: &'static str, token: &str| {
+            last.get_or_format(&HeaderName::from_static(header_name), None, token, || {
+                formats.set(formats.get() + 1);
+                Ok::<_, AuthError>(HeaderValue::try_from(token)?)
+            })
+            .unwrap()
+        };
+
+        // When - formatting the values of two headers twice
+        // Then - each is only formatted once
+        for _ in 0..2 {
+            assert_eq!(get("x-csrf-token", "csrf-1"), "csrf-1");
+            assert_eq!(get("x-api-key", "key-1"), "key-1");
+        }
+        assert_eq!(formats.get(), 2);
+
+        // When - the token of a header changes
+        // Then - only its value is formatted again
+        assert_eq!(get("x-csrf-token", "csrf-2"), "csrf-2");
+        assert_eq!(get("x-api-key", "key-1"), "key-1");
+        assert_eq!(formats.get(), 3);
+    }
+}
@@ -0,0 +1,154 @@
+//! Token fetch and cache metrics, recorded through the `metrics` crate facade with the `metrics` feature.
+//!
+//! Without the feature, recording is a no-op.
+
+use std::future::Future;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// The duration of the token fetches, in seconds, labeled by `outcome` (`success` or `failure`).
+#[cfg(feature = "metrics")]
+pub(crate) const TOKEN_FETCH_DURATION: &str = "reqwest_auth_token_fetch_duration_seconds";
+/// The number of token fetches, labeled by `outcome` (`success` or `failure`).
+#[cfg(feature = "metrics")]
+pub(crate) const TOKEN_FETCHES: &str = "reqwest_auth_token_fetches_total";
+/// The number of tokens served from the cache.
+#[cfg(feature = "metrics")]
+pub(crate) const CACHE_HITS: &str = "reqwest_auth_cache_hits_total";
+/// The number of tokens fetched because the cache had none (or an expired one).
+#[cfg(feature = "metrics")]
+pub(crate) const CACHE_MISSES: &str = "reqwest_auth_cache_misses_total";
+
+/// Records the duration and outcome of a token fetch.
+pub(crate) async fn fetch<T, E>(fetch: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    #[cfg(feature = "metrics")]
+    {
+        let start = Instant::now();
+        let res = fetch.await;
+        let outcome = if res.is_ok() { "success" } else { "failure" };
+        metrics::histogram!(TOKEN_FETCH_DURATION, "outcome" => outcome).record(start.elapsed().as_secs_f64());
+        metrics::counter!(TOKEN_FETCHES, "outcome" => outcome).increment(1);
+        res
+    }
+    #[cfg(not(feature = "metrics"))]
+    fetch.await
+}
+
+/// Records a token served from the cache.
+pub(crate) fn cache_hit() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(CACHE_HITS).increment(1);
+}
+
+/// Records a token missing from the cache.
+pub(crate) fn cache_miss() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(CACHE_MISSES).increment(1);
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use metrics::{
+        Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+    };
+    use std::sync::{Arc, Mutex};
+
+    use super::{cache_hit, cache_miss, fetch};
+
+    /// A recorder keeping track of the recorded metrics, by name and labels.
+    #[derive(Default)]
+    struct TestRecorder {
+        records: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct TestHandle {
+        key: Key,
+        records: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl TestHandle {
+        fn push(&self) {
+            let labels: Vec<String> = self
+                .key
+                .labels()
+                .map(|l| format!("{}={}", l.key(), l.value()))
+                .collect();
+            let record = format!("{}{labels:?}", self.key.name());
+            self.records.lock().unwrap().push(record);
+        }
+    }
+
+    impl CounterFn for TestHandle {
+        fn increment(&self, _value: u64) {
+            self.push();
+        }
+
+        fn absolute(&self, _value: u64) {
+            self.push();
+        }
+    }
+
+    impl HistogramFn for TestHandle {
+        fn record(&self, _value: f64) {
+            self.push();
+        }
+    }
+
+    impl TestRecorder {
+        fn handle(&self, key: &Key) -> Arc<TestHandle> {
+            Arc::new(TestHandle {
+                key: key.clone(),
+                records: self.records.clone(),
+            })
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+        fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+        fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.handle(key))
+        }
+
+        fn register_gauge(&self, _key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+            Histogram::from_arc(self.handle(key))
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        // Given - a recorder
+        let recorder = TestRecorder::default();
+
+        // When - recording fetches and cache lookups
+        metrics::with_local_recorder(&recorder, || {
+            async_std::task::block_on(async {
+                let _ = fetch(async { Ok::<_, ()>("token") }).await;
+                let _ = fetch(async { Err::<(), _>("failure") }).await;
+            });
+            cache_hit();
+            cache_miss();
+        });
+
+        // Then - the metrics are recorded with stable names
+        assert_eq!(
+            *recorder.records.lock().unwrap(),
+            vec![
+                r#"reqwest_auth_token_fetch_duration_seconds["outcome=success"]"#,
+                r#"reqwest_auth_token_fetches_total["outcome=success"]"#,
+                r#"reqwest_auth_token_fetch_duration_seconds["outcome=failure"]"#,
+                r#"reqwest_auth_token_fetches_total["outcome=failure"]"#,
+                "reqwest_auth_cache_hits_total[]",
+                "reqwest_auth_cache_misses_total[]",
+            ]
+        );
+    }
+}
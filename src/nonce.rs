@@ -0,0 +1,102 @@
+use reqwest_middleware::reqwest::header::HeaderName;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::AuthError;
+
+/// AntiReplay
+///
+/// The headers set along the token for the servers rejecting replayed requests, set with
+/// [anti_replay](crate::AuthorizationHeaderMiddlewareBuilder::anti_replay): a timestamp (the unix time, in seconds)
+/// and a nonce (random bytes from the OS secure generator, hex encoded).
+///
+/// A fresh timestamp and nonce are generated each time a request is authorized (including its replays), before
+/// the token is fetched: a [ContextualTokenSource](crate::ContextualTokenSource) can incorporate them in its
+/// token (e.g sign them), through [TokenContext::nonce](crate::TokenContext::nonce) and
+/// [TokenContext::timestamp](crate::TokenContext::timestamp). Such a source must not be cached.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest::header::HeaderName;
+///  use reqwest_auth::AntiReplay;
+///
+///  // 32 random bytes, i.e 64 hex characters
+///  let anti_replay = AntiReplay::new(HeaderName::from_static("x-timestamp"), HeaderName::from_static("x-nonce"))
+///    .nonce_len(32);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AntiReplay {
+    pub(crate) timestamp_header: HeaderName,
+    pub(crate) nonce_header: HeaderName,
+    nonce_len: usize,
+}
+
+impl AntiReplay {
+    /// Creates the anti replay headers with the given names, with nonces of 16 bytes.
+    pub fn new(timestamp_header: HeaderName, nonce_header: HeaderName) -> Self {
+        Self {
+            timestamp_header,
+            nonce_header,
+            nonce_len: 16,
+        }
+    }
+
+    /// Sets the number of random bytes of the nonces, hex encoded in twice as many characters.
+    ///
+    /// Defaults to 16.
+    pub fn nonce_len(mut self, nonce_len: usize) -> Self {
+        self.nonce_len = nonce_len;
+        self
+    }
+
+    /// Generates the timestamp and nonce of a request.
+    pub(crate) fn stamp(&self) -> Result<Stamp, AuthError> {
+        let mut bytes = vec![0; self.nonce_len];
+        getrandom::fill(&mut bytes).map_err(|e| AuthError::Randomness(e.into()))?;
+        let nonce = bytes
+            .iter()
+            .fold(String::with_capacity(2 * bytes.len()), |mut nonce, byte| {
+                let _ = write!(nonce, "{byte:02x}");
+                nonce
+            });
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            .to_string();
+        Ok(Stamp { timestamp, nonce })
+    }
+}
+
+/// The timestamp and nonce generated for a request.
+pub(crate) struct Stamp {
+    pub(crate) timestamp: String,
+    pub(crate) nonce: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::header::HeaderName;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::AntiReplay;
+
+    #[test]
+    fn test_stamp() {
+        // Given - anti replay headers with 8 bytes nonces
+        let anti_replay =
+            AntiReplay::new(HeaderName::from_static("x-timestamp"), HeaderName::from_static("x-nonce")).nonce_len(8);
+
+        // When - stamping requests
+        let first = anti_replay.stamp().unwrap();
+        let second = anti_replay.stamp().unwrap();
+
+        // Then - the nonces are fresh hex strings, and the timestamps the current unix time
+        assert_eq!(first.nonce.len(), 16);
+        assert!(first.nonce.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(first.nonce, second.nonce);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert!(now - first.timestamp.parse::<u64>().unwrap() <= 1);
+    }
+}
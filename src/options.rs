@@ -0,0 +1,219 @@
+use reqwest_middleware::reqwest::header::{HeaderName, HeaderValue, InvalidHeaderName, InvalidHeaderValue};
+use std::time::Duration;
+use url::Host;
+
+use crate::AuthError;
+use crate::AuthorizationHeaderMiddlewareBuilder;
+use crate::CacheStrategy;
+
+/// AuthorizationOptions
+///
+/// The middleware options, as loaded from a configuration file (e.g YAML or TOML) through serde.
+///
+/// Every option is optional, the ones left unset falling back to the middleware defaults. Unknown options
+/// are rejected, so that typos do not go unnoticed.
+///
+/// Available with the `serde` feature.
+///
+/// # How to use
+///
+/// ```rust
+///  # #[derive(Debug)]
+///  # struct MyTokenSource;
+///  # #[async_trait::async_trait]
+///  # impl token_source::TokenSource for MyTokenSource {
+///  #   async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///  #     Ok("my-token".to_string())
+///  #   }
+///  # }
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, AuthorizationOptions};
+///  use std::sync::Arc;
+///
+///  // e.g deserialized from `{ header_name = "x-api-key", ttl_secs = 300, allowed_hosts = ["api.example.com"] }`
+///  let mut options = AuthorizationOptions::default();
+///  options.header_name = Some("x-api-key".to_string());
+///  options.ttl_secs = Some(300);
+///  options.allowed_hosts = Some(vec!["api.example.com".to_string()]);
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::from_options(Arc::new(MyTokenSource), &options).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct AuthorizationOptions {
+    /// The name of the header receiving the token, see
+    /// [header_name](AuthorizationHeaderMiddlewareBuilder::header_name).
+    pub header_name: Option<String>,
+    /// The scheme prefixing the token, see [scheme](AuthorizationHeaderMiddlewareBuilder::scheme).
+    pub scheme: Option<String>,
+    /// How long tokens are cached, in seconds, with the [Blocking](CacheStrategy::Blocking) strategy.
+    pub ttl_secs: Option<u64>,
+    /// The only hosts requests are authorized to, see
+    /// [allowed_hosts](AuthorizationHeaderMiddlewareBuilder::allowed_hosts).
+    pub allowed_hosts: Option<Vec<String>>,
+    /// Whether requests are first sent without authorization, see [lazy](AuthorizationHeaderMiddlewareBuilder::lazy).
+    pub lazy: Option<bool>,
+    /// Whether credentials must only be sent over https, see
+    /// [require_https](AuthorizationHeaderMiddlewareBuilder::require_https).
+    pub require_https: Option<bool>,
+    /// How long the token source is given to provide a token, in seconds, see
+    /// [token_timeout](AuthorizationHeaderMiddlewareBuilder::token_timeout).
+    pub token_timeout_secs: Option<u64>,
+}
+
+/// InvalidOptions
+///
+/// The error raised when building a middleware from invalid [AuthorizationOptions].
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum InvalidOptions {
+    /// The header name is not a valid one.
+    #[error("Invalid header name: {0}")]
+    HeaderName(#[from] InvalidHeaderName),
+    /// The scheme is not valid in a header value.
+    #[error("Invalid scheme: {0}")]
+    Scheme(#[from] InvalidHeaderValue),
+    /// A duration (e.g the TTL) is zero, which would make the option useless.
+    #[error("The {0} option must be strictly positive")]
+    ZeroDuration(&'static str),
+    /// An allowed host is not a valid host name or IP address.
+    #[error("Invalid allowed host: {0}")]
+    Host(String),
+    /// The middleware cannot be built with the options, e.g with a connection specific header name.
+    #[error(transparent)]
+    Build(#[from] AuthError),
+}
+
+impl AuthorizationOptions {
+    /// Applies the options to the builder, validating them.
+    pub(crate) fn apply(
+        &self,
+        mut builder: AuthorizationHeaderMiddlewareBuilder,
+    ) -> Result<AuthorizationHeaderMiddlewareBuilder, InvalidOptions> {
+        if let Some(header_name) = &self.header_name {
+            builder = builder.header_name(HeaderName::try_from(header_name)?);
+        }
+        if let Some(scheme) = &self.scheme {
+            HeaderValue::try_from(scheme)?;
+            builder = builder.scheme(scheme);
+        }
+        if let Some(ttl) = Self::duration("ttl_secs", self.ttl_secs)? {
+            builder = builder.cache_strategy(CacheStrategy::Blocking { ttl });
+        }
+        if let Some(hosts) = &self.allowed_hosts {
+            if let Some(host) = hosts.iter().find(|host| Host::parse(host).is_err()) {
+                return Err(InvalidOptions::Host(host.clone()));
+            }
+            builder = builder.allowed_hosts(hosts);
+        }
+        if let Some(lazy) = self.lazy {
+            builder = builder.lazy(lazy);
+        }
+        if let Some(require_https) = self.require_https {
+            builder = builder.require_https(require_https);
+        }
+        if let Some(token_timeout) = Self::duration("token_timeout_secs", self.token_timeout_secs)? {
+            builder = builder.token_timeout(token_timeout);
+        }
+        Ok(builder)
+    }
+
+    fn duration(option: &'static str, secs: Option<u64>) -> Result<Option<Duration>, InvalidOptions> {
+        match secs {
+            Some(0) => Err(InvalidOptions::ZeroDuration(option)),
+            secs => Ok(secs.map(Duration::from_secs)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use token_source::TokenSource;
+
+    use super::{AuthorizationOptions, InvalidOptions};
+    use crate::AuthError;
+    use crate::AuthorizationHeaderMiddleware;
+
+    #[derive(Debug)]
+    struct MyTokenSource;
+
+    #[async_trait::async_trait]
+    impl TokenSource for MyTokenSource {
+        async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            Ok("my-token".to_string())
+        }
+    }
+
+    #[test]
+    fn test_deserialize() {
+        // Given - options from a configuration file
+        let json = r#"{"header_name": "x-api-key", "ttl_secs": 300, "allowed_hosts": ["api.example.com"]}"#;
+
+        // When - deserializing them
+        let options: AuthorizationOptions = serde_json::from_str(json).unwrap();
+
+        // Then - the options which are not set are left to the middleware defaults
+        let expected = AuthorizationOptions {
+            header_name: Some("x-api-key".to_string()),
+            ttl_secs: Some(300),
+            allowed_hosts: Some(vec!["api.example.com".to_string()]),
+            ..AuthorizationOptions::default()
+        };
+        assert_eq!(options, expected);
+
+        // Then - unknown options are rejected
+        assert!(serde_json::from_str::<AuthorizationOptions>(r#"{"header": "x-api-key"}"#).is_err());
+    }
+
+    fn rejection(options: AuthorizationOptions) -> InvalidOptions {
+        match AuthorizationHeaderMiddleware::from_options(Arc::new(MyTokenSource), &options) {
+            Ok(_) => panic!("The options should have been rejected: {options:?}"),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        // Given - invalid options
+        // When - building a middleware from them
+        // Then - they are rejected at construction
+        let header_name = Some("x api key".to_string());
+        assert!(matches!(
+            rejection(AuthorizationOptions {
+                header_name,
+                ..Default::default()
+            }),
+            InvalidOptions::HeaderName(_)
+        ));
+        let scheme = Some("Bearer\n".to_string());
+        assert!(matches!(
+            rejection(AuthorizationOptions {
+                scheme,
+                ..Default::default()
+            }),
+            InvalidOptions::Scheme(_)
+        ));
+        let ttl_secs = Some(0);
+        assert!(matches!(
+            rejection(AuthorizationOptions {
+                ttl_secs,
+                ..Default::default()
+            }),
+            InvalidOptions::ZeroDuration("ttl_secs")
+        ));
+        let allowed_hosts = Some(vec!["api example".to_string()]);
+        assert!(matches!(
+            rejection(AuthorizationOptions { allowed_hosts, ..Default::default() }),
+            InvalidOptions::Host(host) if host == "api example"
+        ));
+        let header_name = Some("connection".to_string());
+        assert!(matches!(
+            rejection(AuthorizationOptions {
+                header_name,
+                ..Default::default()
+            }),
+            InvalidOptions::Build(AuthError::ConnectionHeaderName { .. })
+        ));
+    }
+}
@@ -0,0 +1,301 @@
+use reqwest_middleware::reqwest::header::RETRY_AFTER;
+use reqwest_middleware::reqwest::{Response, StatusCode};
+use std::time::{Duration, SystemTime};
+
+use crate::Backoff;
+use crate::ExponentialBackoff;
+
+/// RefreshPolicy
+///
+/// When and how requests are replayed with a refreshed token, set with
+/// [refresh_policy](crate::AuthorizationHeaderMiddlewareBuilder::refresh_policy).
+///
+/// When the response of an authorized request has one of the trigger statuses, the token is refreshed and the
+/// request replayed, up to the maximum number of retries. Each retry waits for the backoff (doubled at each retry, or
+/// per the [backoff](crate::AuthorizationHeaderMiddlewareBuilder::backoff) of the middleware when set), or the
+/// `Retry-After` delay of the response (in seconds, or until its date) when honored, capped by the maximum delay.
+///
+/// The defaults match the common OAuth2 behavior: a single immediate retry when the token is rejected (401).
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest::StatusCode;
+///  use reqwest_auth::RefreshPolicy;
+///  use std::time::Duration;
+///
+///  // Also retry when the token is refused (403), up to twice, waiting 100ms then 200ms
+///  let policy = RefreshPolicy::new()
+///    .statuses([StatusCode::UNAUTHORIZED, StatusCode::FORBIDDEN])
+///    .max_retries(2)
+///    .backoff(Duration::from_millis(100));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefreshPolicy {
+    statuses: Vec<StatusCode>,
+    max_retries: u32,
+    backoff: Duration,
+    honor_retry_after: bool,
+    max_delay: Duration,
+}
+
+impl Default for RefreshPolicy {
+    fn default() -> Self {
+        Self {
+            statuses: vec![StatusCode::UNAUTHORIZED],
+            max_retries: 1,
+            backoff: Duration::ZERO,
+            honor_retry_after: true,
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RefreshPolicy {
+    /// Creates the default policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the statuses triggering a refresh and retry.
+    ///
+    /// Defaults to 401 (Unauthorized).
+    pub fn statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Sets the maximum number of retries of a request, zero disabling them.
+    ///
+    /// Defaults to 1.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the wait before the first retry, doubled at each following one.
+    ///
+    /// The [backoff](crate::AuthorizationHeaderMiddlewareBuilder::backoff) of the middleware (if any) replaces it.
+    ///
+    /// Defaults to zero (immediate retries).
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets whether the `Retry-After` delay of the response replaces the backoff.
+    ///
+    /// Both forms of the header are supported: a number of seconds, or an HTTP date (waiting until then, not at all
+    /// once past). Malformed values are ignored.
+    ///
+    /// Defaults to true.
+    pub fn honor_retry_after(mut self, honor_retry_after: bool) -> Self {
+        self.honor_retry_after = honor_retry_after;
+        self
+    }
+
+    /// Sets the maximum wait before a retry, whatever the backoff or `Retry-After` delay.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Returns how long to wait before the given retry (starting at zero) of the request, if it should be retried,
+    /// per the given backoff (if any) rather than the one of the policy.
+    pub(crate) fn retry(&self, retry: u32, res: &Response, backoff: Option<&dyn Backoff>) -> Option<Duration> {
+        if retry >= self.max_retries || !self.statuses.contains(&res.status()) {
+            return None;
+        }
+        let retry_after = res
+            .headers()
+            .get(RETRY_AFTER)
+            .filter(|_| self.honor_retry_after)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| retry_after(value.trim()));
+        let backoff = match backoff {
+            Some(backoff) => backoff.delay(retry),
+            None => ExponentialBackoff::new(self.backoff).delay(retry),
+        };
+        Some(retry_after.unwrap_or(backoff).min(self.max_delay))
+    }
+}
+
+/// Parses a `Retry-After` delay, either in seconds or until an HTTP date (zero once past).
+fn retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// RetryBodyPolicy
+///
+/// What to do with the requests whose body cannot be cloned (i.e streaming bodies), when they may need to be
+/// retried: in [lazy](crate::AuthorizationHeaderMiddlewareBuilder::lazy) mode, with the
+/// [refresh policy](crate::AuthorizationHeaderMiddlewareBuilder::refresh_policy), or in the grace window of the
+/// [CacheStrategy::Grace](crate::CacheStrategy::Grace) strategy.
+///
+/// Set with [retry_body_policy](crate::AuthorizationHeaderMiddlewareBuilder::retry_body_policy).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RetryBodyPolicy {
+    /// Send the request without retries, logging a warning through the [log](https://docs.rs/log) facade.
+    #[default]
+    Skip,
+    /// Read the body into memory so that the request can be retried, when it is at most the given length (in
+    /// bytes). Larger bodies are streamed as is, without retries (as with [RetryBodyPolicy::Skip]).
+    Buffer {
+        /// The maximum length of the buffered bodies, in bytes.
+        max_len: usize,
+    },
+    /// Fail the request with an [AuthError::UncloneableBody](crate::AuthError::UncloneableBody) error.
+    Error,
+}
+
+/// KillSwitchPolicy
+///
+/// What to do with the requests while the [kill switch](crate::AuthorizationHeaderMiddlewareBuilder::kill_switch)
+/// is active.
+///
+/// Set with [kill_switch_policy](crate::AuthorizationHeaderMiddlewareBuilder::kill_switch_policy).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KillSwitchPolicy {
+    /// Fail the requests with an [AuthError::KillSwitch](crate::AuthError::KillSwitch) error (fail closed).
+    #[default]
+    Fail,
+    /// Send the requests without authorization.
+    Proceed,
+}
+
+/// DowngradePolicy
+///
+/// What to do when a redirect followed by the middleware (see
+/// [follow_redirects](crate::AuthorizationHeaderMiddlewareBuilder::follow_redirects)) goes from https to plaintext
+/// http.
+///
+/// The credentials are never sent after a downgrade, whatever the [plaintext
+/// policy](crate::AuthorizationHeaderMiddlewareBuilder::plaintext_policy): whoever controls the redirect (or sits on the
+/// plaintext network path) would get them. Set with
+/// [downgrade_policy](crate::AuthorizationHeaderMiddlewareBuilder::downgrade_policy).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DowngradePolicy {
+    /// Follow the redirect (and the next ones) without the credentials of the middleware.
+    #[default]
+    Strip,
+    /// Return the redirect response, without following it.
+    Stop,
+    /// Fail the request with an [AuthError::InsecureTransport](crate::AuthError::InsecureTransport) error.
+    Error,
+}
+
+/// RefreshFailurePolicy
+///
+/// What to do with the expired cached token when the fetch of its replacement fails.
+///
+/// The policy only matters for the strategies serving expired tokens: with
+/// [BackgroundStaleWhileRevalidate](crate::CacheStrategy::BackgroundStaleWhileRevalidate) and
+/// [Grace](crate::CacheStrategy::Grace), a retained token keeps being served until the end of its stale window, while
+/// a cleared one makes the next requests wait for a new token (failing, or getting the [fallback
+/// token](crate::AuthorizationHeaderMiddlewareBuilder::fallback_static), while the token source is down). Tokens
+/// still within their TTL (e.g when a [scheduled](crate::AuthorizationHeaderMiddlewareBuilder::background_refresh_interval)
+/// refresh fails) are always retained.
+///
+/// Set with [refresh_failure_policy](crate::AuthorizationHeaderMiddlewareBuilder::refresh_failure_policy).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RefreshFailurePolicy {
+    /// Keep the expired token, to ride out outages of the token provider (serve stale).
+    #[default]
+    Retain,
+    /// Drop the expired token, so that it is no longer sent once its replacement could not be fetched (e.g when
+    /// the provider failing may mean that the token was revoked).
+    Clear,
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::header::RETRY_AFTER;
+    use reqwest_middleware::reqwest::{Response, StatusCode};
+    use std::time::{Duration, SystemTime};
+
+    use super::RefreshPolicy;
+
+    fn response(status: StatusCode, retry_after: Option<&str>) -> Response {
+        let mut res = http::Response::builder().status(status);
+        if let Some(retry_after) = retry_after {
+            res = res.header(RETRY_AFTER, retry_after);
+        }
+        Response::from(res.body("").unwrap())
+    }
+
+    #[test]
+    fn test_retry() {
+        // Given - the default policy
+        let policy = RefreshPolicy::new();
+
+        // Then - rejected requests are retried once, right away
+        let unauthorized = response(StatusCode::UNAUTHORIZED, None);
+        assert_eq!(policy.retry(0, &unauthorized, None), Some(Duration::ZERO));
+        assert_eq!(policy.retry(1, &unauthorized, None), None);
+        assert_eq!(policy.retry(0, &response(StatusCode::SERVICE_UNAVAILABLE, None), None), None);
+
+        // Given - a policy with a backoff, retrying unavailable services
+        let policy = RefreshPolicy::new()
+            .statuses([StatusCode::SERVICE_UNAVAILABLE])
+            .max_retries(3)
+            .backoff(Duration::from_secs(2))
+            .max_delay(Duration::from_secs(5));
+
+        // Then - the backoff is doubled at each retry, up to the maximum delay
+        let unavailable = response(StatusCode::SERVICE_UNAVAILABLE, None);
+        let delays: Vec<_> = (0..4).map(|retry| policy.retry(retry, &unavailable, None)).collect();
+        assert_eq!(
+            delays,
+            [2, 4, 5]
+                .map(|secs| Some(Duration::from_secs(secs)))
+                .into_iter()
+                .chain([None])
+                .collect::<Vec<_>>()
+        );
+
+        // Then - the Retry-After delay replaces the backoff when honored
+        let retry_after = response(StatusCode::SERVICE_UNAVAILABLE, Some("1"));
+        assert_eq!(policy.retry(1, &retry_after, None), Some(Duration::from_secs(1)));
+        let policy = policy.honor_retry_after(false);
+        assert_eq!(policy.retry(1, &retry_after, None), Some(Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn test_retry_after_date() {
+        // Given - a policy retrying unavailable services, waiting up to a minute
+        let policy = RefreshPolicy::new()
+            .statuses([StatusCode::SERVICE_UNAVAILABLE])
+            .backoff(Duration::from_secs(2))
+            .max_delay(Duration::from_secs(60));
+
+        // When - the Retry-After header is a date in 30 seconds
+        // Then - the retry waits until then
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(30));
+        let delay = policy
+            .retry(0, &response(StatusCode::SERVICE_UNAVAILABLE, Some(&date)), None)
+            .unwrap();
+        assert!(
+            delay <= Duration::from_secs(30) && delay >= Duration::from_secs(28),
+            "{delay:?}"
+        );
+
+        // When - the date is in the past, or later than the maximum delay
+        // Then - the retry is immediate, or capped
+        let past = response(StatusCode::SERVICE_UNAVAILABLE, Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(policy.retry(0, &past, None), Some(Duration::ZERO));
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(3600));
+        let later = response(StatusCode::SERVICE_UNAVAILABLE, Some(&date));
+        assert_eq!(policy.retry(0, &later, None), Some(Duration::from_secs(60)));
+
+        // When - the header is malformed
+        // Then - the backoff is used
+        let malformed = response(StatusCode::SERVICE_UNAVAILABLE, Some("tomorrow"));
+        assert_eq!(policy.retry(0, &malformed, None), Some(Duration::from_secs(2)));
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use token_source::TokenSource;
+
+/// Why a token is being fetched, see [ReasonAwareTokenSource].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FetchReason {
+    /// No token was fetched yet (or caching is disabled).
+    Initial,
+    /// The cached token expired, per the [cache strategy](crate::CacheStrategy).
+    Expired,
+    /// The server rejected the previous token with a 401 (Unauthorized) status.
+    Rejected,
+}
+
+/// ReasonAwareTokenSource
+///
+/// A token source told why it is being called, e.g to bypass its own cache only when the previous token was
+/// [rejected](FetchReason::Rejected).
+///
+/// Used through the [reason aware builder](crate::AuthorizationHeaderMiddleware::reason_aware_builder).
+#[async_trait::async_trait]
+pub trait ReasonAwareTokenSource: Send + Sync + Debug {
+    /// Returns a valid token, fetched for the given reason.
+    async fn token_for(&self, reason: FetchReason) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Adapts a plain token source, which ignores the reason.
+#[derive(Debug)]
+pub(crate) struct Unaware(pub(crate) Arc<dyn TokenSource>);
+
+#[async_trait::async_trait]
+impl ReasonAwareTokenSource for Unaware {
+    async fn token_for(&self, _reason: FetchReason) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        self.0.token().await
+    }
+}
@@ -0,0 +1,56 @@
+use reqwest_middleware::reqwest::{Request, Response};
+use std::fmt::Debug;
+
+/// What to do with a request still unauthorized, as decided by an [UnauthorizedHook].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Recovery {
+    /// Return the 401 (Unauthorized) response as is.
+    #[default]
+    GiveUp,
+    /// Send the request once more, with a new token.
+    Retry,
+}
+
+/// UnauthorizedHook
+///
+/// Recovers from the requests still rejected with a 401 (Unauthorized) status once the middleware is done retrying
+/// them, set with [on_unauthorized](crate::AuthorizationHeaderMiddlewareBuilder::on_unauthorized).
+///
+/// The hook can do whatever renews the credentials (e.g prompt for a new login in an interactive CLI, or trigger
+/// the provisioning of new credentials), then decide whether the request is sent once more. A failing hook fails
+/// the request with an [AuthError::UnauthorizedHook](crate::AuthError::UnauthorizedHook) error.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest::{Request, Response};
+///  use reqwest_auth::{Recovery, UnauthorizedHook};
+///
+///  // Ask for a new login, then retry with the token it stored
+///  #[derive(Debug)]
+///  struct Relogin;
+///
+///  #[async_trait::async_trait]
+///  impl UnauthorizedHook for Relogin {
+///    async fn recover(
+///      &self,
+///      req: &Request,
+///      res: &Response,
+///    ) -> Result<Recovery, Box<dyn std::error::Error + Send + Sync>> {
+///      println!("{} rejected the credentials, please log in again", req.url());
+///      // Run the login flow, storing the token where the token source reads it
+///      Ok(Recovery::Retry)
+///    }
+///  }
+/// ```
+#[async_trait::async_trait]
+pub trait UnauthorizedHook: Send + Sync + Debug {
+    /// Returns whether the request is sent once more, given its last response.
+    ///
+    /// The request is the one before it was authorized: it does not carry the rejected token.
+    async fn recover(
+        &self,
+        req: &Request,
+        res: &Response,
+    ) -> Result<Recovery, Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,120 @@
+use reqwest_middleware::reqwest::header::{
+    HeaderName, AUTHORIZATION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, LOCATION, PROXY_AUTHORIZATION,
+    TRANSFER_ENCODING, WWW_AUTHENTICATE,
+};
+use reqwest_middleware::reqwest::{Method, Request, Response, StatusCode, Url};
+
+/// The headers describing the body, dropped along with it.
+const BODY_HEADERS: [HeaderName; 4] = [CONTENT_TYPE, CONTENT_LENGTH, CONTENT_ENCODING, TRANSFER_ENCODING];
+
+/// The headers carrying credentials, which are not forwarded to another origin (as by reqwest).
+const SENSITIVE_HEADERS: [HeaderName; 4] = [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, WWW_AUTHENTICATE];
+
+/// Returns the request following the redirect response to the given request (if any).
+///
+/// As per RFC 9110, `303` redirects (but for `HEAD` requests), and `301` and `302` redirects of `POST` requests, are
+/// followed with `GET` requests without body. The other ones are followed with the same method and body, when the
+/// request can be cloned.
+pub(crate) fn follow(req: &Request, res: &Response) -> Option<Request> {
+    let status = res.status();
+    if ![
+        StatusCode::MOVED_PERMANENTLY,
+        StatusCode::FOUND,
+        StatusCode::SEE_OTHER,
+        StatusCode::TEMPORARY_REDIRECT,
+        StatusCode::PERMANENT_REDIRECT,
+    ]
+    .contains(&status)
+    {
+        return None;
+    }
+    let location = res.headers().get(LOCATION)?.to_str().ok()?;
+    let url = req.url().join(location).ok()?;
+    let to_get = match status {
+        StatusCode::SEE_OTHER => req.method() != Method::HEAD,
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND => req.method() == Method::POST,
+        _ => false,
+    };
+    let mut redirect = match to_get {
+        true => {
+            let mut redirect = Request::new(Method::GET, url.clone());
+            *redirect.headers_mut() = req.headers().clone();
+            for name in &BODY_HEADERS {
+                redirect.headers_mut().remove(name);
+            }
+            *redirect.timeout_mut() = req.timeout().copied();
+            redirect
+        }
+        false => req.try_clone()?,
+    };
+    if !same_origin(req.url(), &url) {
+        for name in &SENSITIVE_HEADERS {
+            redirect.headers_mut().remove(name);
+        }
+    }
+    *redirect.url_mut() = url;
+    Some(redirect)
+}
+
+/// Returns whether the redirect goes from https to plaintext http.
+pub(crate) fn is_downgrade(from: &Url, to: &Url) -> bool {
+    from.scheme() == "https" && to.scheme() == "http"
+}
+
+/// Returns whether the two urls have the same scheme, host and port.
+pub(crate) fn same_origin(from: &Url, to: &Url) -> bool {
+    from.scheme() == to.scheme()
+        && from.host_str() == to.host_str()
+        && from.port_or_known_default() == to.port_or_known_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::header::{AUTHORIZATION, CONTENT_TYPE, LOCATION};
+    use reqwest_middleware::reqwest::{Method, Request, Response, StatusCode};
+
+    use super::{follow, is_downgrade};
+
+    fn redirect(status: StatusCode, location: &str) -> Response {
+        Response::from(
+            http::Response::builder()
+                .status(status)
+                .header(LOCATION, location)
+                .body("")
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_follow() {
+        // Given - a POST request with a body
+        let mut req = Request::new(Method::POST, "https://example.com/items/".parse().unwrap());
+        req.headers_mut()
+            .insert(CONTENT_TYPE, "application/json".parse().unwrap());
+        req.headers_mut().insert(AUTHORIZATION, "my-token".parse().unwrap());
+        *req.body_mut() = Some("{}".into());
+
+        // When - following a 303 redirect to a relative location
+        let followed = follow(&req, &redirect(StatusCode::SEE_OTHER, "created")).unwrap();
+
+        // Then - it is a GET request without body, to the same origin
+        assert_eq!(followed.method(), Method::GET);
+        assert_eq!(followed.url().as_str(), "https://example.com/items/created");
+        assert!(followed.body().is_none());
+        assert!(followed.headers().get(CONTENT_TYPE).is_none());
+        assert!(followed.headers().get(AUTHORIZATION).is_some());
+
+        // When - following a 307 redirect to another origin
+        let followed = follow(&req, &redirect(StatusCode::TEMPORARY_REDIRECT, "http://example.com/items")).unwrap();
+
+        // Then - it keeps its method and body, without the credentials
+        assert_eq!(followed.method(), Method::POST);
+        assert!(followed.body().is_some());
+        assert!(followed.headers().get(AUTHORIZATION).is_none());
+        assert!(is_downgrade(req.url(), followed.url()));
+
+        // When - getting another response
+        // Then - it is not followed
+        assert!(follow(&req, &Response::from(http::Response::new(""))).is_none());
+    }
+}
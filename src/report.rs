@@ -0,0 +1,162 @@
+use std::fmt;
+
+use crate::CacheStrategy;
+use crate::RefreshPolicy;
+use crate::RetryBodyPolicy;
+
+/// ConfigReport
+///
+/// A summary of the effective configuration of a middleware, returned by
+/// [describe](crate::AuthorizationHeaderMiddleware::describe), e.g to log it at startup and confirm the deployed
+/// configuration.
+///
+/// The report only names the options: it never holds the tokens (e.g the [fallback
+/// token](crate::AuthorizationHeaderMiddlewareBuilder::fallback_static)), nor the header values matched by the
+/// filters (e.g the [gate](crate::AuthorizationHeaderMiddlewareBuilder::gate_on_header) one). Its
+/// [Display](std::fmt::Display) is a single line, suitable for the logs.
+///
+/// # How to use
+///
+/// ```rust
+///  # #[derive(Debug)]
+///  # struct MyTokenSource;
+///  # #[async_trait::async_trait]
+///  # impl token_source::TokenSource for MyTokenSource {
+///  #   async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///  #     Ok("my-token".to_string())
+///  #   }
+///  # }
+///  use reqwest_auth::AuthorizationHeaderMiddleware;
+///  use std::sync::Arc;
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(MyTokenSource))
+///    .scheme("Bearer")
+///    .build();
+///  let report = auth_middleware.describe();
+///  assert_eq!(report.scheme(), Some("Bearer"));
+///  println!("Authorization middleware: {report}");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigReport {
+    pub(crate) header_name: String,
+    pub(crate) header_name_fn: bool,
+    pub(crate) scheme: Option<String>,
+    pub(crate) lazy: bool,
+    pub(crate) cache_strategy: Option<CacheStrategy>,
+    pub(crate) cache_key: bool,
+    pub(crate) allowed_hosts: Option<Vec<String>>,
+    pub(crate) host_auths: Vec<String>,
+    pub(crate) tag_auths: usize,
+    pub(crate) filters: Vec<&'static str>,
+    pub(crate) refresh_policy: Option<RefreshPolicy>,
+    pub(crate) retry_body_policy: RetryBodyPolicy,
+    pub(crate) max_recoveries: Option<u32>,
+    pub(crate) options: Vec<&'static str>,
+}
+
+impl ConfigReport {
+    /// Returns the name of the header receiving the token, unless it is computed per request.
+    pub fn header_name(&self) -> &str {
+        &self.header_name
+    }
+
+    /// Returns whether the name of the header is [computed per
+    /// request](crate::AuthorizationHeaderMiddlewareBuilder::header_name_fn).
+    pub fn header_name_fn(&self) -> bool {
+        self.header_name_fn
+    }
+
+    /// Returns the scheme prefixing the token (if any).
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme.as_deref()
+    }
+
+    /// Returns whether requests are first sent without authorization (lazy mode).
+    pub fn lazy(&self) -> bool {
+        self.lazy
+    }
+
+    /// Returns the cache strategy of the tokens (if any).
+    pub fn cache_strategy(&self) -> Option<CacheStrategy> {
+        self.cache_strategy
+    }
+
+    /// Returns whether the tokens are cached per [cache key](crate::AuthorizationHeaderMiddlewareBuilder::cache_key).
+    pub fn cache_key(&self) -> bool {
+        self.cache_key
+    }
+
+    /// Returns the allowed hosts, none when the requests to all the hosts are authorized.
+    pub fn allowed_hosts(&self) -> Option<&[String]> {
+        self.allowed_hosts.as_deref()
+    }
+
+    /// Returns the hosts with their own [config](crate::AuthorizationHeaderMiddlewareBuilder::host_auth).
+    pub fn host_auths(&self) -> &[String] {
+        &self.host_auths
+    }
+
+    /// Returns the number of [tag configs](crate::AuthorizationHeaderMiddlewareBuilder::tag_auth).
+    pub fn tag_auths(&self) -> usize {
+        self.tag_auths
+    }
+
+    /// Returns the builder names of the filters deciding which requests are authorized (e.g `skip_loopback`), besides
+    /// the [allowed hosts](Self::allowed_hosts).
+    pub fn filters(&self) -> &[&'static str] {
+        &self.filters
+    }
+
+    /// Returns the policy retrying the rejected requests with a refreshed token (if any).
+    pub fn refresh_policy(&self) -> Option<&RefreshPolicy> {
+        self.refresh_policy.as_ref()
+    }
+
+    /// Returns what is done with the bodies of the requests which may be retried.
+    pub fn retry_body_policy(&self) -> RetryBodyPolicy {
+        self.retry_body_policy
+    }
+
+    /// Returns the maximum number of recoveries of the
+    /// [unauthorized hook](crate::AuthorizationHeaderMiddlewareBuilder::on_unauthorized), none without a hook.
+    pub fn max_recoveries(&self) -> Option<u32> {
+        self.max_recoveries
+    }
+
+    /// Returns the builder names of the other options changed from their defaults (e.g `fallback_static`).
+    pub fn options(&self) -> &[&'static str] {
+        &self.options
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.header_name_fn {
+            true => write!(f, "header: computed per request")?,
+            false => write!(f, "header: {}", self.header_name)?,
+        }
+        write!(f, ", scheme: {}", self.scheme.as_deref().unwrap_or("none"))?;
+        write!(f, ", lazy: {}", self.lazy)?;
+        match &self.cache_strategy {
+            Some(strategy) if self.cache_key => write!(f, ", cache: {strategy:?} per key")?,
+            Some(strategy) => write!(f, ", cache: {strategy:?}")?,
+            None => write!(f, ", cache: none")?,
+        }
+        match &self.allowed_hosts {
+            Some(hosts) => write!(f, ", allowed hosts: [{}]", hosts.join(", "))?,
+            None => write!(f, ", allowed hosts: any")?,
+        }
+        write!(f, ", host auths: [{}]", self.host_auths.join(", "))?;
+        write!(f, ", tag auths: {}", self.tag_auths)?;
+        write!(f, ", filters: [{}]", self.filters.join(", "))?;
+        match &self.refresh_policy {
+            Some(policy) => write!(f, ", refresh policy: {policy:?}")?,
+            None => write!(f, ", refresh policy: none")?,
+        }
+        write!(f, ", retry body policy: {:?}", self.retry_body_policy)?;
+        if let Some(max_recoveries) = self.max_recoveries {
+            write!(f, ", max recoveries: {max_recoveries}")?;
+        }
+        write!(f, ", options: [{}]", self.options.join(", "))
+    }
+}
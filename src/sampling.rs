@@ -0,0 +1,51 @@
+use std::sync::Mutex;
+
+/// Selects a fraction of the requests.
+pub(crate) struct Sampler {
+    rate: f64,
+    rng: Mutex<fastrand::Rng>,
+}
+
+impl Sampler {
+    /// Creates a sampler selecting the given fraction (clamped between 0 and 1) of the requests,
+    /// using a random generator initialized with the given seed (if any).
+    pub(crate) fn new(rate: f64, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+        Self {
+            rate: rate.clamp(0.0, 1.0),
+            rng: Mutex::new(rng),
+        }
+    }
+
+    /// Returns whether the current request is selected.
+    pub(crate) fn sample(&self) -> bool {
+        self.rng.lock().unwrap().f64() < self.rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sampler;
+
+    #[test]
+    fn test_sampler() {
+        // Given - samplers with extreme rates
+        // Then - they select all or nothing
+        let all = Sampler::new(1.0, None);
+        let none = Sampler::new(0.0, None);
+        assert!((0..100).all(|_| all.sample()));
+        assert!((0..100).all(|_| !none.sample()));
+
+        // Given - two samplers with the same seed
+        // Then - they select the same requests, roughly at the given rate
+        let first = Sampler::new(0.25, Some(42));
+        let second = Sampler::new(0.25, Some(42));
+        let selections = (0..1000).map(|_| first.sample()).collect::<Vec<_>>();
+        assert_eq!(selections, (0..1000).map(|_| second.sample()).collect::<Vec<_>>());
+        let selected = selections.iter().filter(|selected| **selected).count();
+        assert!((200..300).contains(&selected), "{selected} selected");
+    }
+}
@@ -0,0 +1,243 @@
+use std::ffi::OsString;
+use std::fmt::{Debug, Display, Formatter};
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use token_source::TokenSource;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+use crate::{Clock, SystemClock};
+
+/// An error running the command of a [CommandTokenSource].
+#[derive(Debug)]
+pub enum CommandError {
+    /// The command could not be started, e.g because the program is not installed.
+    Spawn {
+        /// The program of the command.
+        program: String,
+        /// The cause of the failure.
+        source: std::io::Error,
+    },
+    /// The command exited with a failure status.
+    Failed {
+        /// The program of the command.
+        program: String,
+        /// The exit status of the command.
+        status: ExitStatus,
+        /// The error output of the command, trimmed.
+        stderr: String,
+    },
+    /// The command succeeded without printing a token (or printing one that is not UTF-8).
+    InvalidOutput {
+        /// The program of the command.
+        program: String,
+    },
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Spawn { program, source } => write!(f, "The command {program} could not be started: {source}"),
+            Self::Failed {
+                program,
+                status,
+                stderr,
+            } if stderr.is_empty() => write!(f, "The command {program} failed ({status})"),
+            Self::Failed {
+                program,
+                status,
+                stderr,
+            } => write!(f, "The command {program} failed ({status}): {stderr}"),
+            Self::InvalidOutput { program } => write!(f, "The command {program} did not print a UTF-8 token"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Spawn { source, .. } => Some(source),
+            Self::Failed { .. } | Self::InvalidOutput { .. } => None,
+        }
+    }
+}
+
+/// CommandTokenSource
+///
+/// A token source running an external command printing the token on its standard output, such as the auth
+/// helpers of cloud CLIs (e.g `gcloud auth print-access-token`).
+///
+/// The output is trimmed of its surrounding whitespace (e.g the trailing newline). The command is run on the
+/// first request, then its token is cached for the [ttl](Self::ttl), concurrent requests waiting for the same run.
+/// A failing command (or one printing nothing) fails the fetch, with its exit status and error output.
+///
+/// Commands need a tokio runtime.
+///
+/// Available with the `command` feature.
+///
+/// # How to use
+///
+/// ```rust,no_run
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, CommandTokenSource};
+///  use std::sync::Arc;
+///  use std::time::Duration;
+///
+///  let ts = CommandTokenSource::new("gcloud")
+///    .args(["auth", "print-access-token"])
+///    // gcloud access tokens are valid for an hour
+///    .ttl(Duration::from_secs(50 * 60));
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(ts)).scheme("Bearer").build();
+/// ```
+pub struct CommandTokenSource {
+    program: OsString,
+    args: Vec<OsString>,
+    ttl: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    cache: Mutex<Option<(String, Instant)>>,
+}
+
+impl CommandTokenSource {
+    /// Creates a source running the given program, without arguments.
+    pub fn new(program: impl Into<OsString>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            ttl: None,
+            clock: Arc::new(SystemClock),
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Adds the given arguments to the command.
+    pub fn args<T: Into<OsString>>(mut self, args: impl IntoIterator<Item = T>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets how long the token is cached before the command is run again, zero disabling the cache.
+    ///
+    /// By default, the token is cached for the lifetime of the source.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the clock used to expire the token.
+    ///
+    /// Defaults to the [SystemClock].
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn program(&self) -> String {
+        self.program.to_string_lossy().into_owned()
+    }
+
+    async fn run(&self) -> Result<String, CommandError> {
+        let output = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|source| CommandError::Spawn {
+                program: self.program(),
+                source,
+            })?;
+        if !output.status.success() {
+            return Err(CommandError::Failed {
+                program: self.program(),
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        match String::from_utf8(output.stdout) {
+            Ok(token) if !token.trim().is_empty() => Ok(token.trim().to_string()),
+            _ => Err(CommandError::InvalidOutput {
+                program: self.program(),
+            }),
+        }
+    }
+}
+
+impl Debug for CommandTokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Never show the cached token
+        f.debug_struct("CommandTokenSource")
+            .field("program", &self.program)
+            .field("args", &self.args)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for CommandTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut cache = self.cache.lock().await;
+        let now = self.clock.now();
+        if let Some((token, fetched_at)) = cache.as_ref() {
+            if self.ttl.is_none_or(|ttl| now.duration_since(*fetched_at) < ttl) {
+                return Ok(token.clone());
+            }
+        }
+        let token = self.run().await?;
+        *cache = Some((token.clone(), now));
+        Ok(token)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use token_source::TokenSource;
+
+    use super::{CommandError, CommandTokenSource};
+    use crate::TestClock;
+
+    #[tokio::test]
+    async fn test_command() {
+        // Given - a command printing a new token at each run
+        let counter = std::env::temp_dir().join(format!("reqwest-auth-command-{}", std::process::id()));
+        let script = format!(
+            "echo run >> {0}; echo \"  token-$(wc -l < {0} | tr -d ' ')\"",
+            counter.display()
+        );
+        let clock = Arc::new(TestClock::new());
+        let ts = CommandTokenSource::new("sh")
+            .args(["-c", &script])
+            .ttl(Duration::from_secs(60))
+            .clock(clock.clone());
+
+        // When - fetching tokens
+        // Then - the trimmed output is cached for the ttl, then the command is run again
+        assert_eq!(ts.token().await.unwrap(), "token-1");
+        assert_eq!(ts.token().await.unwrap(), "token-1");
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(ts.token().await.unwrap(), "token-2");
+        assert!(!format!("{ts:?}").contains("token-2"));
+        std::fs::remove_file(counter).unwrap();
+
+        // Then - failures are reported with the exit status and the error output
+        let err = CommandTokenSource::new("sh")
+            .args(["-c", "echo 'not logged in' >&2; exit 3"])
+            .token()
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<CommandError>().unwrap();
+        assert!(matches!(err, CommandError::Failed { status, .. } if status.code() == Some(3)));
+        assert_eq!(err.to_string(), "The command sh failed (exit status: 3): not logged in");
+
+        // Then - commands printing nothing, or that cannot be started, fail
+        let err = CommandTokenSource::new("true").token().await.unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(CommandError::InvalidOutput { .. })));
+        let err = CommandTokenSource::new("reqwest-auth-missing-program")
+            .token()
+            .await
+            .unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(CommandError::Spawn { .. })));
+    }
+}
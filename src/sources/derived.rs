@@ -0,0 +1,134 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use token_source::TokenSource;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type DeriveFuture = Pin<Box<dyn Future<Output = Result<String, BoxError>> + Send>>;
+type Transform = Arc<dyn Fn(String) -> DeriveFuture + Send + Sync>;
+
+/// The error of the transform of a [DerivedTokenSource], telling it apart from the errors of its base source.
+#[derive(Debug)]
+pub struct DerivationError(BoxError);
+
+impl Display for DerivationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Token derivation failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for DerivationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+/// DerivedTokenSource
+///
+/// A token source deriving its tokens from the ones of a base source, through a (possibly async) transform,
+/// e.g to sign the base token, or exchange it for another one.
+///
+/// The transform is given each base token, and returns the derived one. The errors of the base source are
+/// returned unchanged, while the ones of the transform are wrapped in a [DerivationError]. Derived sources are
+/// token sources themselves, so that derivations can be chained.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, DerivedTokenSource, IdentityTokenSource};
+///  use std::sync::Arc;
+///
+///  let ts = DerivedTokenSource::new(Arc::new(IdentityTokenSource::new("my-token")), |token| async move {
+///    // e.g a call to a signing service
+///    Ok(format!("{token}.signature"))
+///  });
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::from(Arc::new(ts));
+/// ```
+pub struct DerivedTokenSource {
+    base: Arc<dyn TokenSource>,
+    transform: Transform,
+}
+
+impl DerivedTokenSource {
+    /// Creates a source deriving the tokens of the base source with the given async transform.
+    pub fn new<F, Fut>(base: Arc<dyn TokenSource>, transform: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, BoxError>> + Send + 'static,
+    {
+        Self {
+            base,
+            transform: Arc::new(move |token| Box::pin(transform(token))),
+        }
+    }
+
+    /// Creates a source deriving the tokens of the base source with the given infallible, synchronous transform.
+    pub fn map<F>(base: Arc<dyn TokenSource>, transform: F) -> Self
+    where
+        F: Fn(String) -> String + Send + Sync + 'static,
+    {
+        Self::new(base, move |token| std::future::ready(Ok(transform(token))))
+    }
+}
+
+impl Debug for DerivedTokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DerivedTokenSource")
+            .field("base", &self.base)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for DerivedTokenSource {
+    async fn token(&self) -> Result<String, BoxError> {
+        let token = self.base.token().await?;
+        (self.transform)(token).await.map_err(|e| DerivationError(e).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use token_source::TokenSource;
+
+    use super::{DerivationError, DerivedTokenSource};
+    use crate::sources::mock::MockTokenSource;
+
+    #[async_std::test]
+    async fn test_derived() {
+        // Given - chained derivations of a base source
+        let base = Arc::new(
+            MockTokenSource::new()
+                .then_token("token")
+                .then_error("provider unavailable"),
+        );
+        let signed = DerivedTokenSource::new(base.clone(), |token| async move {
+            match token.as_str() {
+                "token" => Ok(format!("{token}.signature")),
+                _ => Err("unknown token".into()),
+            }
+        });
+        let ts = DerivedTokenSource::map(Arc::new(signed), |token| token.to_uppercase());
+
+        // When - fetching tokens
+        // Then - they are derived from the base ones, in order
+        assert_eq!(ts.token().await.unwrap(), "TOKEN.SIGNATURE");
+
+        // Then - the errors of the base source are returned unchanged
+        let err = ts.token().await.unwrap_err();
+        assert!(err.downcast_ref::<DerivationError>().is_none());
+
+        // Then - the errors of the transform are told apart
+        let ts = DerivedTokenSource::new(Arc::new(MockTokenSource::new().then_token("other")), |_| async {
+            Err("signing service unavailable".into())
+        });
+        let err = ts.token().await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<DerivationError>().unwrap().to_string(),
+            "Token derivation failed: signing service unavailable"
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use md5::Md5;
+use sha2::digest::Digest;
+use sha2::Sha256;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+use crate::challenge;
+use crate::{ContextualTokenSource, TokenContext};
+
+type RealmCredentials = Arc<dyn Fn(&str) -> Option<(String, String)> + Send + Sync>;
+
+/// An error answering an HTTP Digest challenge.
+#[derive(Debug)]
+pub enum DigestError {
+    /// The challenge is not a well formed Digest one.
+    InvalidChallenge(String),
+    /// The challenge requires a hash algorithm which is not supported.
+    UnsupportedAlgorithm(String),
+    /// The challenge requires a quality of protection which is not supported (e.g `auth-int`).
+    UnsupportedQop(String),
+    /// There are no credentials for the realm of the challenge.
+    UnknownRealm(String),
+}
+
+impl Display for DigestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidChallenge(challenge) => write!(f, "Invalid Digest challenge: {challenge}"),
+            Self::UnsupportedAlgorithm(algorithm) => write!(f, "Unsupported Digest algorithm: {algorithm}"),
+            Self::UnsupportedQop(qop) => write!(f, "Unsupported Digest quality of protection: {qop}"),
+            Self::UnknownRealm(realm) => write!(f, "No Digest credentials for the realm {realm}"),
+        }
+    }
+}
+
+impl std::error::Error for DigestError {}
+
+/// DigestTokenSource
+///
+/// A token source answering the HTTP Digest challenge (`WWW-Authenticate: Digest ...`) of the server
+/// ([RFC 7616](https://www.rfc-editor.org/rfc/rfc7616)), with the `MD5` and `SHA-256` algorithms.
+///
+/// The challenge is read from the unauthorized first attempt of the request, through the
+/// [challenge_header](crate::AuthorizationHeaderMiddlewareBuilder::challenge_header) option, which must be set
+/// to the `WWW-Authenticate` header. Requests without a challenge are sent without authorization.
+///
+/// The provided tokens contain the `Digest` scheme: do not configure one on the middleware.
+/// Only the `auth` quality of protection is supported (or none, for legacy servers), the request body is
+/// not part of the digest.
+///
+/// Available with the `digest` feature.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest::header::WWW_AUTHENTICATE;
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, DigestTokenSource};
+///  use std::sync::Arc;
+///
+///  let ts = DigestTokenSource::new("john", "password");
+///  let auth_middleware = AuthorizationHeaderMiddleware::contextual_builder(Arc::new(ts))
+///    .challenge_header(WWW_AUTHENTICATE)
+///    .build();
+/// ```
+pub struct DigestTokenSource {
+    credentials: Credentials,
+}
+
+/// The credentials answering the challenges.
+enum Credentials {
+    Fixed { username: String, password: String },
+    PerRealm(RealmCredentials),
+}
+
+impl DigestTokenSource {
+    /// Creates a source answering the challenges with the given credentials.
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            credentials: Credentials::Fixed {
+                username: username.into(),
+                password: password.into(),
+            },
+        }
+    }
+
+    /// Creates a source answering the challenges with the credentials (username and password) selected for their
+    /// realm, for the servers with several realms.
+    ///
+    /// Challenges of realms without credentials fail with a [DigestError::UnknownRealm] error.
+    ///
+    /// ```rust
+    ///  use reqwest_auth::DigestTokenSource;
+    ///
+    ///  let ts = DigestTokenSource::per_realm(|realm| match realm {
+    ///    "admin@example.com" => Some(("admin".to_string(), "admin-password".to_string())),
+    ///    "users@example.com" => Some(("john".to_string(), "password".to_string())),
+    ///    _ => None,
+    ///  });
+    /// ```
+    pub fn per_realm<F>(credentials: F) -> Self
+    where
+        F: Fn(&str) -> Option<(String, String)> + Send + Sync + 'static,
+    {
+        Self {
+            credentials: Credentials::PerRealm(Arc::new(credentials)),
+        }
+    }
+
+    /// Computes the Authorization header value answering the challenge, for the given method, uri and client nonce.
+    fn respond(&self, challenge: &str, method: &str, uri: &str, cnonce: &str) -> Result<String, DigestError> {
+        let invalid = || DigestError::InvalidChallenge(challenge.to_string());
+        let params = challenge::parse(challenge)
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Digest"))
+            .map(|(_, params)| params)
+            .ok_or_else(invalid)?;
+        let realm = params.get("realm").ok_or_else(invalid)?;
+        let (username, password) = match &self.credentials {
+            Credentials::Fixed { username, password } => (username.clone(), password.clone()),
+            Credentials::PerRealm(credentials) => {
+                credentials(realm).ok_or_else(|| DigestError::UnknownRealm(realm.clone()))?
+            }
+        };
+        let nonce = params.get("nonce").ok_or_else(invalid)?;
+        let algorithm = params.get("algorithm").map_or("MD5", String::as_str);
+        let hash: fn(&str) -> String = match algorithm.to_ascii_uppercase().as_str() {
+            "MD5" => |data| format!("{:x}", Md5::digest(data)),
+            "SHA-256" => |data| format!("{:x}", Sha256::digest(data)),
+            _ => return Err(DigestError::UnsupportedAlgorithm(algorithm.to_string())),
+        };
+        let qop = match params.get("qop") {
+            Some(qop) if qop.split(',').any(|qop| qop.trim() == "auth") => Some("auth"),
+            Some(qop) => return Err(DigestError::UnsupportedQop(qop.clone())),
+            None => None,
+        };
+
+        let ha1 = hash(&format!("{username}:{realm}:{password}"));
+        let ha2 = hash(&format!("{method}:{uri}"));
+        let mut value = format!(r#"Digest username="{username}", realm="{realm}", nonce="{nonce}", uri="{uri}""#);
+        match qop {
+            // The nonce is fresh from the challenge: this is the first time it is used
+            Some(qop) => {
+                let response = hash(&format!("{ha1}:{nonce}:00000001:{cnonce}:{qop}:{ha2}"));
+                value += &format!(r#", response="{response}", qop={qop}, nc=00000001, cnonce="{cnonce}""#);
+            }
+            None => value += &format!(r#", response="{}""#, hash(&format!("{ha1}:{nonce}:{ha2}"))),
+        }
+        value += &format!(", algorithm={algorithm}");
+        if let Some(opaque) = params.get("opaque") {
+            value += &format!(r#", opaque="{opaque}""#);
+        }
+        Ok(value)
+    }
+}
+
+impl Debug for DigestTokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Never show the password
+        let mut debug = f.debug_struct("DigestTokenSource");
+        if let Credentials::Fixed { username, .. } = &self.credentials {
+            debug.field("username", username);
+        }
+        debug.finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl ContextualTokenSource for DigestTokenSource {
+    async fn token_with(
+        &self,
+        ctx: &TokenContext<'_>,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(challenge) = ctx.challenge() else {
+            return Ok(None);
+        };
+        let uri = match ctx.url().query() {
+            Some(query) => format!("{}?{query}", ctx.url().path()),
+            None => ctx.url().path().to_string(),
+        };
+        let cnonce = format!("{:016x}", fastrand::u64(..));
+        Ok(Some(self.respond(challenge, ctx.method().as_str(), &uri, &cnonce)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DigestError, DigestTokenSource};
+
+    // The example of RFC 7616 (section 3.9.1)
+    const CHALLENGE: &str = r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm=ALGORITHM, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#;
+    const CNONCE: &str = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+
+    #[test]
+    fn test_respond() {
+        let ts = DigestTokenSource::new("Mufasa", "Circle of Life");
+        for (algorithm, response) in [
+            ("MD5", "8ca523f5e9506fed4657c9700eebdbec"),
+            ("SHA-256", "753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1"),
+        ] {
+            // Given - a challenge with the given algorithm
+            let challenge = CHALLENGE.replace("ALGORITHM", algorithm);
+
+            // When - answering it
+            let value = ts.respond(&challenge, "GET", "/dir/index.html", CNONCE).unwrap();
+
+            // Then - the response is the expected one
+            assert_eq!(
+                value,
+                format!(
+                    r#"Digest username="Mufasa", realm="http-auth@example.org", nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", uri="/dir/index.html", response="{response}", qop=auth, nc=00000001, cnonce="{CNONCE}", algorithm={algorithm}, opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#
+                )
+            );
+        }
+        assert!(!format!("{ts:?}").contains("Circle"));
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let ts = DigestTokenSource::new("Mufasa", "Circle of Life");
+        let respond = |challenge: &str| ts.respond(challenge, "GET", "/", CNONCE).unwrap_err();
+
+        assert!(matches!(
+            respond(r#"Digest realm="r", nonce="n", algorithm=SHA-512-256"#),
+            DigestError::UnsupportedAlgorithm(algorithm) if algorithm == "SHA-512-256"
+        ));
+        assert!(matches!(
+            respond(r#"Digest realm="r", nonce="n", qop="auth-int""#),
+            DigestError::UnsupportedQop(_)
+        ));
+        assert!(matches!(respond(r#"Basic realm="r""#), DigestError::InvalidChallenge(_)));
+        assert!(matches!(respond(r#"Digest realm="r""#), DigestError::InvalidChallenge(_)));
+    }
+
+    #[test]
+    fn test_per_realm() {
+        // Given - a source with credentials for a single realm
+        let ts = DigestTokenSource::per_realm(|realm| {
+            (realm == "http-auth@example.org").then(|| ("Mufasa".to_string(), "Circle of Life".to_string()))
+        });
+
+        // When - answering a challenge of that realm
+        // Then - its credentials are used
+        let value = ts
+            .respond(&CHALLENGE.replace("ALGORITHM", "MD5"), "GET", "/dir/index.html", CNONCE)
+            .unwrap();
+        assert!(value.contains(r#"response="8ca523f5e9506fed4657c9700eebdbec""#));
+
+        // When - answering a challenge of another realm
+        // Then - it fails
+        assert!(matches!(
+            ts.respond(r#"Digest realm="other", nonce="n""#, "GET", "/", CNONCE).unwrap_err(),
+            DigestError::UnknownRealm(realm) if realm == "other"
+        ));
+    }
+}
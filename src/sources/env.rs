@@ -0,0 +1,126 @@
+use reqwest_middleware::reqwest::header::{HeaderName, HeaderValue, InvalidHeaderName, InvalidHeaderValue};
+use std::sync::Arc;
+use token_source::TokenSource;
+
+use crate::AuthError;
+use crate::AuthorizationHeaderMiddleware;
+use crate::AuthorizationHeaderMiddlewareBuilder;
+
+/// The environment variable holding the token, see [from_env](AuthorizationHeaderMiddleware::from_env).
+const TOKEN_VAR: &str = "REQWEST_AUTH_TOKEN";
+/// The environment variable holding the scheme, see [from_env](AuthorizationHeaderMiddleware::from_env).
+const SCHEME_VAR: &str = "REQWEST_AUTH_SCHEME";
+/// The environment variable holding the header name, see [from_env](AuthorizationHeaderMiddleware::from_env).
+const HEADER_VAR: &str = "REQWEST_AUTH_HEADER";
+
+/// EnvError
+///
+/// The error raised when the environment does not provide a valid configuration, see [EnvTokenSource] and
+/// [from_env](AuthorizationHeaderMiddleware::from_env).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum EnvError {
+    /// A required variable is not set, empty, or not unicode.
+    #[error("The {0} environment variable is not set")]
+    Missing(String),
+    /// The header name variable is not a valid header name.
+    #[error("Invalid header name in {HEADER_VAR}: {0}")]
+    HeaderName(#[from] InvalidHeaderName),
+    /// The scheme variable is not valid in a header value.
+    #[error("Invalid scheme in {SCHEME_VAR}: {0}")]
+    Scheme(#[from] InvalidHeaderValue),
+    /// The middleware cannot be built from the variables, e.g with a connection specific header name.
+    #[error("Invalid configuration in {HEADER_VAR}: {0}")]
+    Build(#[from] AuthError),
+}
+
+/// EnvTokenSource
+///
+/// A token source reading the token from an environment variable, e.g injected by the container orchestrator.
+///
+/// The variable is read at each fetch, trimmed of its surrounding whitespace: cache the tokens with a
+/// [cache strategy](crate::AuthorizationHeaderMiddlewareBuilder::cache_strategy) when it does not change. A
+/// missing (or empty) variable fails the fetch with an [EnvError::Missing] error.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, EnvTokenSource};
+///  use std::sync::Arc;
+///
+///  let ts = EnvTokenSource::new("API_TOKEN");
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(ts)).scheme("Bearer").build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct EnvTokenSource {
+    name: String,
+}
+
+impl EnvTokenSource {
+    /// Creates a source reading the given environment variable.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+
+    fn read(&self) -> Result<String, EnvError> {
+        var(&self.name).ok_or_else(|| EnvError::Missing(self.name.clone()))
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for EnvTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.read()?)
+    }
+}
+
+/// Returns the trimmed value of the given environment variable, none when it is not set, empty or not unicode.
+fn var(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Configures a builder per the environment variables, see [from_env](AuthorizationHeaderMiddleware::from_env).
+pub(crate) fn builder() -> Result<AuthorizationHeaderMiddlewareBuilder, EnvError> {
+    let ts = EnvTokenSource::new(TOKEN_VAR);
+    // Fail at construction rather than on the first request
+    ts.read()?;
+    let mut builder = AuthorizationHeaderMiddleware::builder(Arc::new(ts));
+    if let Some(header_name) = var(HEADER_VAR) {
+        builder = builder.header_name(HeaderName::try_from(header_name)?);
+    }
+    if let Some(scheme) = var(SCHEME_VAR) {
+        HeaderValue::try_from(&scheme)?;
+        builder = builder.scheme(scheme);
+    }
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use token_source::TokenSource;
+
+    use super::{EnvError, EnvTokenSource};
+
+    #[tokio::test]
+    async fn test_env() {
+        // Given - a source reading a variable which is not set
+        let name = format!("REQWEST_AUTH_TEST_TOKEN_{}", std::process::id());
+        let ts = EnvTokenSource::new(&name);
+
+        // When - fetching a token
+        // Then - the fetch fails, naming the variable
+        let err = ts.token().await.unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(EnvError::Missing(missing)) if *missing == name));
+
+        // When - setting the variable
+        // Then - its trimmed value is read at each fetch
+        std::env::set_var(&name, " my-token\n");
+        assert_eq!(ts.token().await.unwrap(), "my-token");
+        std::env::set_var(&name, "my-new-token");
+        assert_eq!(ts.token().await.unwrap(), "my-new-token");
+        std::env::remove_var(&name);
+    }
+}
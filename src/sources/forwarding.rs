@@ -0,0 +1,117 @@
+use reqwest_middleware::reqwest::header::{HeaderMap, AUTHORIZATION};
+use std::fmt::{Debug, Formatter};
+
+use crate::{ContextualTokenSource, TokenContext};
+
+/// ForwardedToken
+///
+/// The token of an incoming request, to be forwarded to the upstream by a [ForwardingTokenSource].
+///
+/// The middleware only sees the outgoing requests: place the incoming token in their extensions, wrapped in a
+/// [TokenSourceContext](crate::TokenSourceContext).
+#[derive(Clone)]
+pub struct ForwardedToken(String);
+
+impl ForwardedToken {
+    /// Wraps the given token, without its scheme.
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    /// Extracts the bearer token of the `Authorization` header of the incoming request, if any.
+    ///
+    /// The scheme is matched case insensitively and removed: configure the `Bearer` one on the middleware.
+    pub fn bearer(headers: &HeaderMap) -> Option<Self> {
+        let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+        let (scheme, token) = value.split_once(' ')?;
+        let token = token.trim();
+        (scheme.eq_ignore_ascii_case("Bearer") && !token.is_empty()).then(|| Self::new(token))
+    }
+}
+
+impl Debug for ForwardedToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Never show the token
+        f.debug_tuple("ForwardedToken").finish_non_exhaustive()
+    }
+}
+
+/// ForwardingTokenSource
+///
+/// A token source forwarding the token of the incoming request to the upstream, for token passthrough gateways
+/// and proxies.
+///
+/// The incoming token is read from the [ForwardedToken] context of the outgoing request. Requests without it
+/// are sent without authorization, so that the upstream rejects them as it would the incoming request.
+///
+/// As the forwarded tokens depend on the request, they are not cached.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, ForwardedToken, ForwardingTokenSource, TokenSourceContext};
+///  use reqwest_middleware::reqwest::header::HeaderMap;
+///  use std::sync::Arc;
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::contextual_builder(Arc::new(ForwardingTokenSource))
+///    .scheme("Bearer")
+///    .build();
+///
+///  // e.g the headers of the incoming request, in a handler of the gateway
+///  # let incoming_headers = HeaderMap::new();
+///  if let Some(token) = ForwardedToken::bearer(&incoming_headers) {
+///    let ctx = TokenSourceContext::new(token);
+///    // Then attach it using `reqwest_middleware::RequestBuilder::with_extension(ctx)`
+///  }
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ForwardingTokenSource;
+
+#[async_trait::async_trait]
+impl ContextualTokenSource for ForwardingTokenSource {
+    async fn token_with(
+        &self,
+        ctx: &TokenContext<'_>,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(ctx.value::<ForwardedToken>().map(|token| token.0.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+    use reqwest_middleware::reqwest::{Method, Url};
+
+    use super::{ForwardedToken, ForwardingTokenSource};
+    use crate::{ContextualTokenSource, TokenContext, TokenSourceContext};
+
+    #[async_std::test]
+    async fn test_forwarding() {
+        // Given - the headers of an incoming request
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("bearer caller-token"));
+
+        // When - forwarding its token
+        let ctx = TokenSourceContext::new(ForwardedToken::bearer(&headers).unwrap());
+        let url = Url::parse("https://upstream.example.com").unwrap();
+        let token = ForwardingTokenSource
+            .token_with(&TokenContext {
+                method: &Method::GET,
+                url: &url,
+                value: Some(&ctx),
+                challenge: None,
+                stamp: None,
+            })
+            .await
+            .unwrap();
+
+        // Then - the token is provided without its scheme, and never shown
+        assert_eq!(token.unwrap(), "caller-token");
+        assert!(!format!("{:?}", ctx.get::<ForwardedToken>().unwrap()).contains("caller"));
+
+        // Then - only bearer tokens are extracted
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Basic am9objpzZWNyZXQ="));
+        assert!(ForwardedToken::bearer(&headers).is_none());
+        assert!(ForwardedToken::bearer(&HeaderMap::new()).is_none());
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use token_source::TokenSource;
+
+/// FnTokenSource
+///
+/// A token source calling an async function, to migrate code fetching its tokens itself and setting the header on
+/// each request to the middleware, without rewriting the fetch as a [TokenSource].
+///
+/// Code written for the manual header often returns the token along its scheme (e.g `Bearer my-token`): with
+/// [strip_scheme](Self::strip_scheme), the source drops it from the tokens starting with it, so that the middleware
+/// setting the scheme does not send it twice, whichever was returned.
+///
+/// # How to use
+///
+/// Before, with the header set on each request:
+///
+/// ```rust,no_run
+///  # async fn fetch_token() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///  #   Ok("my-token".to_string())
+///  # }
+///  # async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+///  use reqwest_middleware::reqwest::header::AUTHORIZATION;
+///
+///  let client = reqwest_middleware::reqwest::Client::new();
+///  let token = fetch_token().await?;
+///  client
+///    .get("https://example.com")
+///    .header(AUTHORIZATION, format!("Bearer {token}"))
+///    .send()
+///    .await?;
+///  # Ok(())
+///  # }
+/// ```
+///
+/// After, with the same function wrapped by the middleware:
+///
+/// ```rust,no_run
+///  # async fn fetch_token() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///  #   Ok("my-token".to_string())
+///  # }
+///  # async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, FnTokenSource};
+///  use std::sync::Arc;
+///
+///  let ts = FnTokenSource::new(fetch_token).strip_scheme("Bearer");
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(ts))
+///    .scheme("Bearer")
+///    .build();
+///  let client = reqwest_middleware::ClientBuilder::new(reqwest_middleware::reqwest::Client::new())
+///    .with(auth_middleware)
+///    .build();
+///  client.get("https://example.com").send().await?;
+///  # Ok(())
+///  # }
+/// ```
+///
+/// Or, with the default options, [from_token_fn_with_scheme](crate::AuthorizationHeaderMiddleware::from_token_fn_with_scheme).
+pub struct FnTokenSource<F> {
+    f: F,
+    scheme: Option<String>,
+}
+
+impl<F, Fut> FnTokenSource<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<String, Box<dyn std::error::Error + Send + Sync>>> + Send,
+{
+    /// Creates a source calling the given function for each token.
+    pub fn new(f: F) -> Self {
+        Self { f, scheme: None }
+    }
+
+    /// Drops the given scheme (compared case-insensitively) from the tokens starting with it.
+    ///
+    /// By default, the tokens are provided as returned.
+    pub fn strip_scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.scheme = Some(scheme.into());
+        self
+    }
+}
+
+/// Returns the token without the given scheme prefix and its separating spaces (if any).
+fn strip_scheme(token: String, scheme: &str) -> String {
+    match token.get(..scheme.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(scheme) && token[scheme.len()..].starts_with(' ') => {
+            token[scheme.len()..].trim_start_matches(' ').to_string()
+        }
+        _ => token,
+    }
+}
+
+impl<F> Debug for FnTokenSource<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnTokenSource")
+            .field("scheme", &self.scheme)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl<F, Fut> TokenSource for FnTokenSource<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<String, Box<dyn std::error::Error + Send + Sync>>> + Send,
+{
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let token = (self.f)().await?;
+        Ok(match &self.scheme {
+            Some(scheme) => strip_scheme(token, scheme),
+            None => token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use token_source::TokenSource;
+
+    use super::FnTokenSource;
+
+    #[async_std::test]
+    async fn test_fn_token_source() {
+        // Given - a function returning the token along its scheme
+        let ts = FnTokenSource::new(|| async { Ok("bearer  my-token".to_string()) }).strip_scheme("Bearer");
+
+        // Then - the scheme is dropped
+        assert_eq!(ts.token().await.unwrap(), "my-token");
+
+        // Given - a function returning the bare token
+        let ts = FnTokenSource::new(|| async { Ok("Bearertoken".to_string()) }).strip_scheme("Bearer");
+
+        // Then - it is provided as is
+        assert_eq!(ts.token().await.unwrap(), "Bearertoken");
+
+        // Given - a failing function
+        let ts = FnTokenSource::new(|| async { Err("No token".into()) });
+
+        // Then - its error is returned
+        assert_eq!(ts.token().await.unwrap_err().to_string(), "No token");
+    }
+}
@@ -0,0 +1,58 @@
+use token_source::TokenSource;
+
+/// IdentityTokenSource
+///
+/// A token source providing a fixed identity, e.g derived from the subject of the client certificate, for
+/// services expecting it in a header alongside mutual TLS.
+///
+/// The certificate itself is configured on the reqwest client (see `reqwest::Identity`): this source only provides
+/// the header value, set by the middleware like any other token.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, IdentityTokenSource};
+///  use reqwest_middleware::reqwest::header::HeaderName;
+///  use std::sync::Arc;
+///
+///  let ts = IdentityTokenSource::new("CN=billing-service,O=Example");
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(ts))
+///    .header_name(HeaderName::from_static("x-client-identity"))
+///    .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct IdentityTokenSource {
+    identity: String,
+}
+
+impl IdentityTokenSource {
+    /// Creates a source providing the given identity.
+    pub fn new(identity: impl Into<String>) -> Self {
+        Self {
+            identity: identity.into(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for IdentityTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.identity.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use token_source::TokenSource;
+
+    use super::IdentityTokenSource;
+
+    #[async_std::test]
+    async fn test_identity() {
+        // Given - a source for a client identity
+        let ts = IdentityTokenSource::new("CN=billing-service,O=Example");
+
+        // Then - the identity is provided as is
+        assert_eq!(ts.token().await.unwrap(), "CN=billing-service,O=Example");
+    }
+}
@@ -0,0 +1,149 @@
+use keyring::Entry;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Mutex;
+use token_source::TokenSource;
+
+/// An error reading a token from the OS keychain.
+#[derive(Debug)]
+pub enum KeychainError {
+    /// There is no entry for the service and user.
+    NotFound {
+        /// The service of the entry.
+        service: String,
+        /// The user of the entry.
+        user: String,
+    },
+    /// The keychain could not be accessed, typically because it is locked.
+    Locked(keyring::Error),
+    /// Any other keychain failure.
+    Keychain(keyring::Error),
+}
+
+impl Display for KeychainError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound { service, user } => {
+                write!(f, "No keychain entry for service {service} and user {user}")
+            }
+            Self::Locked(e) => write!(f, "The keychain could not be accessed, it may be locked: {e}"),
+            Self::Keychain(e) => write!(f, "Keychain failure: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for KeychainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NotFound { .. } => None,
+            Self::Locked(e) | Self::Keychain(e) => Some(e),
+        }
+    }
+}
+
+/// KeychainTokenSource
+///
+/// A token source reading the token from the OS secure store (macOS keychain, Windows credential manager,
+/// Linux kernel keyutils), using the [keyring] crate.
+///
+/// The token is read from the keychain on the first request, then cached for the lifetime of the source.
+/// Note that reading the keychain is a blocking call, which only happens once.
+///
+/// Available with the `keychain` feature.
+///
+/// # How to use
+///
+/// ```rust,no_run
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, KeychainTokenSource};
+///  use std::sync::Arc;
+///
+///  let ts = KeychainTokenSource::new("my-app", "john").unwrap();
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(ts)).scheme("Bearer").build();
+/// ```
+pub struct KeychainTokenSource {
+    entry: Entry,
+    service: String,
+    user: String,
+    cache: Mutex<Option<String>>,
+}
+
+impl KeychainTokenSource {
+    /// Creates a source reading the keychain entry of the given service and user.
+    pub fn new(service: &str, user: &str) -> Result<Self, KeychainError> {
+        let entry = Entry::new(service, user).map_err(KeychainError::Keychain)?;
+        Ok(Self::from_entry(entry, service, user))
+    }
+
+    /// Creates a source reading the given keychain entry, for the service and user it was created for.
+    pub fn from_entry(entry: Entry, service: &str, user: &str) -> Self {
+        Self {
+            entry,
+            service: service.to_string(),
+            user: user.to_string(),
+            cache: Mutex::new(None),
+        }
+    }
+
+    fn read(&self) -> Result<String, KeychainError> {
+        self.entry.get_password().map_err(|e| match e {
+            keyring::Error::NoEntry => KeychainError::NotFound {
+                service: self.service.clone(),
+                user: self.user.clone(),
+            },
+            keyring::Error::NoStorageAccess(_) => KeychainError::Locked(e),
+            _ => KeychainError::Keychain(e),
+        })
+    }
+}
+
+impl Debug for KeychainTokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Never show the cached token
+        f.debug_struct("KeychainTokenSource")
+            .field("service", &self.service)
+            .field("user", &self.user)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for KeychainTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(token) = cache.as_ref() {
+            return Ok(token.clone());
+        }
+        let token = self.read()?;
+        *cache = Some(token.clone());
+        Ok(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keyring::Entry;
+    use token_source::TokenSource;
+
+    use super::KeychainTokenSource;
+
+    #[async_std::test]
+    async fn test_keychain() {
+        // Given - the platform independent mock credential store
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+
+        // When - reading an entry holding a token
+        // Then - the token is provided
+        let entry = Entry::new("my-app", "john").unwrap();
+        entry.set_password("my-token").unwrap();
+        let ts = KeychainTokenSource::from_entry(entry, "my-app", "john");
+        assert_eq!(ts.token().await.unwrap(), "my-token");
+        assert!(!format!("{ts:?}").contains("my-token"));
+
+        // When - reading a missing entry
+        // Then - a clear error is returned
+        let ts = KeychainTokenSource::new("my-app", "jane").unwrap();
+        assert_eq!(
+            ts.token().await.unwrap_err().to_string(),
+            "No keychain entry for service my-app and user jane"
+        );
+    }
+}
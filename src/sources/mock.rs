@@ -0,0 +1,140 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use token_source::TokenSource;
+
+/// A programmed response of the [MockTokenSource].
+#[derive(Clone, Debug)]
+enum Response {
+    Token(String),
+    Error(String),
+}
+
+/// MockTokenSource
+///
+/// A token source with programmed responses, for the tests of the code using the middleware.
+///
+/// The responses (tokens or errors, possibly delayed) are served in order, the last one being repeated once all
+/// are served. The calls are counted, to assert how many tokens were fetched (e.g that they are cached).
+///
+/// Delays need a tokio runtime.
+///
+/// Available with the `testing` feature.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, MockTokenSource};
+///  use std::sync::Arc;
+///  use std::time::Duration;
+///
+///  // The first fetch fails, the next ones provide a token after 10ms
+///  let ts = Arc::new(
+///    MockTokenSource::new()
+///      .then_error("provider unavailable")
+///      .then_delay(Duration::from_millis(10))
+///      .then_token("my-token"),
+///  );
+///  let auth_middleware = AuthorizationHeaderMiddleware::from(ts.clone());
+///
+///  // Then assert how many tokens were fetched, none yet as no request was sent
+///  ts.assert_calls(0);
+/// ```
+#[derive(Debug, Default)]
+pub struct MockTokenSource {
+    responses: Vec<(Duration, Response)>,
+    delay: Duration,
+    calls: AtomicUsize,
+}
+
+impl MockTokenSource {
+    /// Creates a source without any programmed response, failing all the fetches.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Programs the next response to be the given token.
+    pub fn then_token(self, token: impl Into<String>) -> Self {
+        self.then(Response::Token(token.into()))
+    }
+
+    /// Programs the next response to be an error with the given message.
+    pub fn then_error(self, message: impl Into<String>) -> Self {
+        self.then(Response::Error(message.into()))
+    }
+
+    /// Delays the next programmed response by the given duration.
+    pub fn then_delay(mut self, delay: Duration) -> Self {
+        self.delay += delay;
+        self
+    }
+
+    fn then(mut self, response: Response) -> Self {
+        self.responses.push((std::mem::take(&mut self.delay), response));
+        self
+    }
+
+    /// Returns how many tokens were fetched so far.
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    /// Asserts that the given number of tokens were fetched so far.
+    ///
+    /// # Panics
+    ///
+    /// If another number of tokens were fetched.
+    #[track_caller]
+    pub fn assert_calls(&self, expected: usize) {
+        let calls = self.calls();
+        assert_eq!(calls, expected, "expected {expected} token fetches, got {calls}");
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for MockTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let index = self.calls.fetch_add(1, Ordering::SeqCst);
+        let Some((delay, response)) = self.responses.get(index).or(self.responses.last()) else {
+            return Err("no programmed response".into());
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(*delay).await;
+        }
+        match response {
+            Response::Token(token) => Ok(token.clone()),
+            Response::Error(message) => Err(message.clone().into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use token_source::TokenSource;
+
+    use super::MockTokenSource;
+
+    #[tokio::test]
+    async fn test_mock() {
+        // Given - a source with programmed responses
+        let ts = MockTokenSource::new()
+            .then_token("token-1")
+            .then_error("provider unavailable")
+            .then_delay(Duration::from_millis(20))
+            .then_token("token-2");
+
+        // When - fetching tokens
+        // Then - the responses are served in order, the last one being repeated
+        assert_eq!(ts.token().await.unwrap(), "token-1");
+        assert_eq!(ts.token().await.unwrap_err().to_string(), "provider unavailable");
+        for _ in 0..2 {
+            let start = Instant::now();
+            assert_eq!(ts.token().await.unwrap(), "token-2");
+            assert!(start.elapsed() >= Duration::from_millis(20));
+        }
+        ts.assert_calls(4);
+
+        // Then - a source without responses fails
+        assert!(MockTokenSource::new().token().await.is_err());
+    }
+}
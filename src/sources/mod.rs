@@ -0,0 +1,33 @@
+//! Built-in token source implementations.
+
+#[cfg(feature = "basic")]
+pub(crate) mod basic;
+#[cfg(feature = "command")]
+pub(crate) mod command;
+pub(crate) mod derived;
+#[cfg(feature = "digest")]
+pub(crate) mod digest;
+pub(crate) mod env;
+pub(crate) mod forwarding;
+pub(crate) mod function;
+pub(crate) mod identity;
+#[cfg(feature = "jwt")]
+pub(crate) mod jwt;
+#[cfg(feature = "keychain")]
+pub(crate) mod keychain;
+#[cfg(any(test, feature = "testing"))]
+pub(crate) mod mock;
+#[cfg(feature = "netrc")]
+pub(crate) mod netrc;
+#[cfg(feature = "oidc")]
+pub(crate) mod oidc;
+#[cfg(feature = "oidc")]
+pub(crate) mod refresh;
+#[cfg(any(test, feature = "testing"))]
+pub(crate) mod replay;
+#[cfg(feature = "secrecy")]
+pub(crate) mod secret;
+#[cfg(feature = "tower")]
+pub(crate) mod service;
+pub(crate) mod versioned;
+pub(crate) mod weighted;
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::sources::basic::{basic_credentials, Base64Encoding};
+use crate::ContextualTokenSource;
+use crate::TokenContext;
+
+/// What to do when the netrc file has no entry for the request host.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissingNetrcEntry {
+    /// Fail the request.
+    #[default]
+    Error,
+    /// Send the request without authorization.
+    Skip,
+}
+
+#[derive(Clone, Debug)]
+struct Credentials {
+    login: String,
+    password: String,
+}
+
+/// NetrcTokenSource
+///
+/// A [ContextualTokenSource] looking up the request host in a netrc file,
+/// and providing Basic credentials from the matching entry (or the `default` one).
+///
+/// The provided tokens already contain the `Basic` scheme: do not configure a scheme on the middleware.
+///
+/// Available with the `netrc` feature.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, MissingNetrcEntry, NetrcTokenSource};
+///  use std::sync::Arc;
+///
+///  let ts = NetrcTokenSource::parse("machine api.example.com login john password secret")
+///    // Send requests to other hosts without authorization
+///    .on_missing(MissingNetrcEntry::Skip);
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::contextual_builder(Arc::new(ts)).build();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct NetrcTokenSource {
+    machines: HashMap<String, Credentials>,
+    default: Option<Credentials>,
+    on_missing: MissingNetrcEntry,
+}
+
+impl NetrcTokenSource {
+    /// Reads the netrc file at the given path.
+    pub fn from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// Reads the netrc file of the current user: the one pointed by the `NETRC` environment variable if set,
+    /// `~/.netrc` otherwise.
+    pub fn from_default_file() -> std::io::Result<Self> {
+        let path = match std::env::var_os("NETRC") {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let home = std::env::var_os("HOME")
+                    .or_else(|| std::env::var_os("USERPROFILE"))
+                    .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no home directory"))?;
+                PathBuf::from(home).join(".netrc")
+            }
+        };
+        Self::from_file(path)
+    }
+
+    /// Parses the content of a netrc file.
+    ///
+    /// Macro definitions are ignored, as well as entries without login or password.
+    pub fn parse(content: &str) -> Self {
+        let mut source = Self::default();
+        // The entry being parsed: its machine (none for the default entry), login and password
+        let mut entry: Option<(Option<String>, Option<String>, Option<String>)> = None;
+        let mut lines = content.lines();
+        while let Some(line) = lines.next() {
+            let mut tokens = line.split_whitespace();
+            while let Some(token) = tokens.next() {
+                match token {
+                    "machine" | "default" | "macdef" => {
+                        source.push(entry.take());
+                        match token {
+                            "machine" => entry = tokens.next().map(|name| (Some(name.to_string()), None, None)),
+                            "default" => entry = Some((None, None, None)),
+                            // Skip the macro definition, ending with an empty line
+                            _ => {
+                                lines.by_ref().take_while(|line| !line.trim().is_empty()).for_each(drop);
+                                break;
+                            }
+                        }
+                    }
+                    "login" | "password" | "account" => {
+                        let value = tokens.next().map(str::to_string);
+                        if let Some((_, login, password)) = entry.as_mut() {
+                            match token {
+                                "login" => *login = value,
+                                "password" => *password = value,
+                                _ => {}
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        source.push(entry);
+        source
+    }
+
+    /// Sets what to do when the netrc file has no entry for the request host.
+    ///
+    /// Defaults to [MissingNetrcEntry::Error].
+    pub fn on_missing(mut self, on_missing: MissingNetrcEntry) -> Self {
+        self.on_missing = on_missing;
+        self
+    }
+
+    fn push(&mut self, entry: Option<(Option<String>, Option<String>, Option<String>)>) {
+        if let Some((machine, Some(login), Some(password))) = entry {
+            let credentials = Credentials { login, password };
+            match machine {
+                // The first matching entry wins
+                Some(machine) => {
+                    self.machines.entry(machine.to_ascii_lowercase()).or_insert(credentials);
+                }
+                None => {
+                    self.default.get_or_insert(credentials);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ContextualTokenSource for NetrcTokenSource {
+    async fn token_with(
+        &self,
+        ctx: &TokenContext<'_>,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let host = ctx.url().host_str().unwrap_or_default().to_ascii_lowercase();
+        match self.machines.get(&host).or(self.default.as_ref()) {
+            Some(credentials) => Ok(Some(basic_credentials(
+                &credentials.login,
+                &credentials.password,
+                Base64Encoding::Standard,
+            ))),
+            None if self.on_missing == MissingNetrcEntry::Skip => Ok(None),
+            None => Err(format!("No netrc entry for host {host}").into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::{Method, Url};
+
+    use super::{MissingNetrcEntry, NetrcTokenSource};
+    use crate::{ContextualTokenSource, TokenContext};
+
+    const NETRC: &str = "
+machine api.example.com
+  login john
+  password secret
+
+macdef init
+  machine ignored.example.com login nope password nope
+
+machine other.example.com login jane password pa55 account main
+";
+
+    async fn token(ts: &NetrcTokenSource, url: &str) -> Result<Option<String>, String> {
+        let url = Url::parse(url).unwrap();
+        let ctx = TokenContext {
+            method: &Method::GET,
+            url: &url,
+            value: None,
+            challenge: None,
+            stamp: None,
+        };
+        ts.token_with(&ctx).await.map_err(|e| e.to_string())
+    }
+
+    #[async_std::test]
+    async fn test_netrc() {
+        // Given - a netrc file with a few machines
+        let ts = NetrcTokenSource::parse(NETRC);
+
+        // When - requesting known hosts
+        // Then - their credentials are provided
+        assert_eq!(
+            token(&ts, "https://api.example.com/path").await.unwrap().unwrap(),
+            "Basic am9objpzZWNyZXQ="
+        );
+        assert_eq!(
+            token(&ts, "https://OTHER.example.com").await.unwrap().unwrap(),
+            "Basic amFuZTpwYTU1"
+        );
+
+        // When - requesting unknown hosts (including the ones in macro definitions)
+        // Then - it errors, or skips per config
+        assert!(token(&ts, "https://ignored.example.com").await.is_err());
+        let ts = ts.on_missing(MissingNetrcEntry::Skip);
+        assert_eq!(token(&ts, "https://unknown.example.com").await.unwrap(), None);
+    }
+
+    #[async_std::test]
+    async fn test_netrc_default() {
+        // Given - a netrc file with a default entry
+        let ts = NetrcTokenSource::parse(
+            "machine api.example.com login john password secret\ndefault login anonymous password guest",
+        );
+
+        // When - requesting an unknown host
+        // Then - the default credentials are provided
+        assert_eq!(
+            token(&ts, "https://unknown.example.com").await.unwrap().unwrap(),
+            "Basic YW5vbnltb3VzOmd1ZXN0"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use reqwest_middleware::reqwest::{Client, StatusCode, Url};
+use serde::Deserialize;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use token_source::TokenSource;
+use tokio::sync::Mutex;
+
+use crate::sources::oidc::{http_lifetime, TokenResponse};
+use crate::{Clock, SystemClock};
+
+/// An error refreshing an access token with a refresh token.
+#[derive(Debug)]
+pub enum RefreshTokenError {
+    /// The token request could not be sent, or its response parsed.
+    Request(reqwest_middleware::reqwest::Error),
+    /// The refresh token is invalid, expired or revoked (`invalid_grant`): the user has to authorize the client
+    /// again to get a new one.
+    InvalidGrant {
+        /// The description of the error sent by the server, if any.
+        description: Option<String>,
+    },
+    /// The token endpoint rejected the request for another reason.
+    Rejected {
+        /// The status of the response.
+        status: StatusCode,
+        /// The body of the response (e.g an OAuth2 error).
+        body: String,
+    },
+}
+
+impl Display for RefreshTokenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Request(e) => write!(f, "Token request failed: {e}"),
+            Self::InvalidGrant { description: None } => write!(f, "The refresh token is no longer valid"),
+            Self::InvalidGrant {
+                description: Some(description),
+            } => write!(f, "The refresh token is no longer valid: {description}"),
+            Self::Rejected { status, body } => write!(f, "Token request rejected with status {status}: {body}"),
+        }
+    }
+}
+
+impl std::error::Error for RefreshTokenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(e) => Some(e),
+            Self::InvalidGrant { .. } | Self::Rejected { .. } => None,
+        }
+    }
+}
+
+/// An error response of an OAuth2 token endpoint.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+/// The state of the source, locked while refreshing so that concurrent requests wait for the same refresh.
+struct State {
+    refresh_token: String,
+    access_token: Option<(String, Instant)>,
+}
+
+/// RefreshTokenSource
+///
+/// A token source exchanging a refresh token for access tokens at an OAuth2 token endpoint (`refresh_token`
+/// grant).
+///
+/// Access tokens are cached until they expire (minus a [skew](Self::expiry_skew)), with the same lifetime rules
+/// as the [ClientCredentialsSource](crate::ClientCredentialsSource). When the server rotates the refresh token
+/// (returning a new one along with the access token), the new one is used for the next refresh: it only lives in
+/// memory, the initial refresh token being used again after a restart.
+///
+/// The client authenticates with its id and secret (`client_secret_basic`), or just its id for public clients.
+/// Once the refresh token is rejected, requests fail with a [RefreshTokenError::InvalidGrant] error.
+///
+/// The provided tokens do not contain the scheme: configure the `Bearer` one on the middleware.
+///
+/// Available with the `oidc` feature.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, RefreshTokenSource};
+///  use std::sync::Arc;
+///
+///  let ts = RefreshTokenSource::new("https://auth.example.com/token".parse().unwrap(), "my-app", "refresh-token")
+///    .client_secret("secret")
+///    .scopes(["orders:read"]);
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(ts)).scheme("Bearer").build();
+/// ```
+pub struct RefreshTokenSource {
+    token_endpoint: Url,
+    client_id: String,
+    client_secret: Option<String>,
+    scopes: Vec<String>,
+    client: Client,
+    clock: Arc<dyn Clock>,
+    expiry_skew: Duration,
+    default_ttl: Option<Duration>,
+    state: Mutex<State>,
+}
+
+impl RefreshTokenSource {
+    /// Creates a source refreshing the given refresh token at the token endpoint, for the given (public) client.
+    pub fn new(token_endpoint: Url, client_id: impl Into<String>, refresh_token: impl Into<String>) -> Self {
+        Self {
+            token_endpoint,
+            client_id: client_id.into(),
+            client_secret: None,
+            scopes: Vec::new(),
+            client: Client::new(),
+            clock: Arc::new(SystemClock),
+            expiry_skew: Duration::from_secs(30),
+            default_ttl: None,
+            state: Mutex::new(State {
+                refresh_token: refresh_token.into(),
+                access_token: None,
+            }),
+        }
+    }
+
+    /// Sets the secret of a confidential client.
+    ///
+    /// By default, the client is a public one, only sending its id.
+    pub fn client_secret(mut self, client_secret: impl Into<String>) -> Self {
+        self.client_secret = Some(client_secret.into());
+        self
+    }
+
+    /// Sets the scopes to request, which must have been granted to the refresh token.
+    ///
+    /// By default, no scope is requested: the access tokens get all the scopes of the refresh token.
+    pub fn scopes<T: Into<String>>(mut self, scopes: impl IntoIterator<Item = T>) -> Self {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the client used to reach the token endpoint (e.g with its own TLS settings).
+    ///
+    /// Clones of a `reqwest::Client` share its connection pool: pass the same client to all the token sources (and
+    /// to the client of the middleware) to reuse their connections. It is a plain client, never going through the
+    /// middleware, so that fetching a token cannot recurse into authorizing the token request.
+    ///
+    /// Defaults to a client with default settings, i.e its own connection pool.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sets how long before their expiry access tokens are refreshed.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn expiry_skew(mut self, expiry_skew: Duration) -> Self {
+        self.expiry_skew = expiry_skew;
+        self
+    }
+
+    /// Sets how long access tokens are cached when the token response does not tell their lifetime.
+    ///
+    /// By default, such tokens are not cached.
+    pub fn default_ttl(mut self, default_ttl: Duration) -> Self {
+        self.default_ttl = Some(default_ttl);
+        self
+    }
+
+    /// Sets the clock used to expire the access tokens.
+    ///
+    /// Defaults to the [SystemClock].
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Exchanges the refresh token for a new access token, returning it along with its lifetime (if known)
+    /// and the new refresh token (if rotated).
+    async fn fetch(&self, refresh_token: &str) -> Result<(TokenResponse, Option<Duration>), RefreshTokenError> {
+        let mut form = vec![("grant_type", "refresh_token"), ("refresh_token", refresh_token)];
+        let scope = self.scopes.join(" ");
+        if !scope.is_empty() {
+            form.push(("scope", &scope));
+        }
+        let req = match &self.client_secret {
+            Some(client_secret) => self
+                .client
+                .post(self.token_endpoint.clone())
+                .basic_auth(&self.client_id, Some(client_secret)),
+            None => {
+                form.push(("client_id", &self.client_id));
+                self.client.post(self.token_endpoint.clone())
+            }
+        };
+        let res = req.form(&form).send().await.map_err(RefreshTokenError::Request)?;
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(match serde_json::from_str::<ErrorResponse>(&body) {
+                Ok(error) if error.error == "invalid_grant" => RefreshTokenError::InvalidGrant {
+                    description: error.error_description,
+                },
+                _ => RefreshTokenError::Rejected { status, body },
+            });
+        }
+        let lifetime = http_lifetime(res.headers());
+        let token: TokenResponse = res.json().await.map_err(RefreshTokenError::Request)?;
+        let lifetime = match lifetime {
+            Some(lifetime) => lifetime,
+            None => token.expires_in.map(Duration::from_secs).or(self.default_ttl),
+        };
+        Ok((token, lifetime))
+    }
+}
+
+impl Debug for RefreshTokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Never show the client secret, nor the tokens
+        f.debug_struct("RefreshTokenSource")
+            .field("token_endpoint", &self.token_endpoint.as_str())
+            .field("client_id", &self.client_id)
+            .field("scopes", &self.scopes)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for RefreshTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut state = self.state.lock().await;
+        if let Some((token, expires_at)) = state.access_token.as_ref() {
+            if self.clock.now() < *expires_at {
+                return Ok(token.clone());
+            }
+        }
+        let fetched_at = self.clock.now();
+        let (token, lifetime) = self.fetch(&state.refresh_token).await?;
+        if let Some(refresh_token) = token.refresh_token {
+            state.refresh_token = refresh_token;
+        }
+        state.access_token = lifetime.map(|lifetime| {
+            let expires_at = fetched_at + lifetime.saturating_sub(self.expiry_skew);
+            (token.access_token.clone(), expires_at)
+        });
+        Ok(token.access_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use token_source::TokenSource;
+
+    use super::{RefreshTokenError, RefreshTokenSource};
+    use crate::sources::oidc::tests::serve;
+    use crate::TestClock;
+
+    #[tokio::test]
+    async fn test_refresh_token() {
+        // Given - a token endpoint rotating the refresh tokens, the first one having been revoked once used
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let url = serve({
+            let requests = requests.clone();
+            move |req| {
+                requests.lock().unwrap().push(req.body.clone());
+                match req.body.as_str() {
+                    "grant_type=refresh_token&refresh_token=rt-1&client_id=my-app" => (
+                        "200 OK",
+                        String::new(),
+                        r#"{"access_token":"at-1","expires_in":60,"refresh_token":"rt-2"}"#.to_string(),
+                    ),
+                    "grant_type=refresh_token&refresh_token=rt-2&client_id=my-app" => (
+                        "200 OK",
+                        String::new(),
+                        r#"{"access_token":"at-2","expires_in":60}"#.to_string(),
+                    ),
+                    _ => (
+                        "400 Bad Request",
+                        String::new(),
+                        r#"{"error":"invalid_grant","error_description":"Token revoked"}"#.to_string(),
+                    ),
+                }
+            }
+        })
+        .await;
+        let clock = Arc::new(TestClock::new());
+        let endpoint = format!("{url}/token").parse().unwrap();
+        let ts = RefreshTokenSource::new(endpoint, "my-app", "rt-1").clock(clock.clone());
+
+        // When - fetching tokens before their expiry (minus the skew)
+        // Then - a single access token is requested
+        assert_eq!(ts.token().await.unwrap(), "at-1");
+        clock.advance(Duration::from_secs(29));
+        assert_eq!(ts.token().await.unwrap(), "at-1");
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        // When - fetching tokens once expired
+        // Then - the rotated refresh token is used, then kept when not rotated
+        for _ in 0..2 {
+            clock.advance(Duration::from_secs(30));
+            assert_eq!(ts.token().await.unwrap(), "at-2");
+        }
+        assert_eq!(requests.lock().unwrap().len(), 3);
+
+        // When - the refresh token is revoked
+        let endpoint = format!("{url}/token").parse().unwrap();
+        let revoked = RefreshTokenSource::new(endpoint, "my-app", "rt-0");
+        let err = revoked.token().await.unwrap_err();
+
+        // Then - the invalid grant is reported as such
+        assert!(matches!(
+            err.downcast_ref::<RefreshTokenError>(),
+            Some(RefreshTokenError::InvalidGrant { description: Some(description) }) if description == "Token revoked"
+        ));
+        assert!(!format!("{ts:?}").contains("rt-"));
+    }
+}
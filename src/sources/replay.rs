@@ -0,0 +1,167 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use token_source::TokenSource;
+
+/// RecordingTokenSource
+///
+/// A token source wrapping another one, recording the tokens it provides so that they can be saved
+/// to a fixture file and served by a [ReplayTokenSource] afterward (e.g in CI, without reaching the real provider).
+///
+/// Fixture files contain one token per line.
+///
+/// Available with the `testing` feature.
+///
+/// # How to use
+///
+/// ```rust,no_run
+///  # async fn run(real_ts: std::sync::Arc<dyn token_source::TokenSource>) -> std::io::Result<()> {
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, RecordingTokenSource};
+///  use std::sync::Arc;
+///
+///  let ts = Arc::new(RecordingTokenSource::new(real_ts));
+///  let auth_middleware = AuthorizationHeaderMiddleware::from(ts.clone());
+///
+///  // Run the test against the real provider, then save its tokens
+///  ts.save("tests/fixtures/tokens.txt")?;
+///  # Ok(())
+///  # }
+/// ```
+#[derive(Debug)]
+pub struct RecordingTokenSource {
+    inner: Arc<dyn TokenSource>,
+    tokens: Mutex<Vec<String>>,
+}
+
+impl RecordingTokenSource {
+    /// Creates a source recording the tokens of the given one.
+    pub fn new(inner: Arc<dyn TokenSource>) -> Self {
+        Self {
+            inner,
+            tokens: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the tokens recorded so far, in order.
+    pub fn tokens(&self) -> Vec<String> {
+        self.tokens.lock().unwrap().clone()
+    }
+
+    /// Saves the tokens recorded so far to the given fixture file.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut content = self.tokens().join("\n");
+        content.push('\n');
+        std::fs::write(path, content)
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for RecordingTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let token = self.inner.token().await?;
+        self.tokens.lock().unwrap().push(token.clone());
+        Ok(token)
+    }
+}
+
+/// ReplayTokenSource
+///
+/// A token source serving recorded tokens in order, e.g from a fixture saved by a [RecordingTokenSource].
+///
+/// Once all the tokens are served, fetching another one fails: the test diverged from the recording.
+///
+/// Available with the `testing` feature.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, ReplayTokenSource};
+///  use std::sync::Arc;
+///
+///  let ts = ReplayTokenSource::new(["token-1", "token-2"]);
+///  let auth_middleware = AuthorizationHeaderMiddleware::from(Arc::new(ts));
+/// ```
+#[derive(Debug)]
+pub struct ReplayTokenSource {
+    tokens: Vec<String>,
+    next: AtomicUsize,
+}
+
+impl ReplayTokenSource {
+    /// Creates a source serving the given tokens.
+    pub fn new<T: Into<String>>(tokens: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            tokens: tokens.into_iter().map(Into::into).collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Creates a source serving the tokens of the given fixture file.
+    pub fn from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Self::new(content.lines().filter(|line| !line.is_empty())))
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for ReplayTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let index = self.next.fetch_add(1, Ordering::SeqCst);
+        match self.tokens.get(index) {
+            Some(token) => Ok(token.clone()),
+            None => Err(format!("no recorded token left, {} were recorded", self.tokens.len()).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest;
+    use reqwest_middleware::ClientBuilder;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use token_source::TokenSource;
+
+    use super::{RecordingTokenSource, ReplayTokenSource};
+    use crate::AuthorizationHeaderMiddleware;
+
+    /// A token source standing for a real provider.
+    #[derive(Debug, Default)]
+    struct ProviderTokenSource(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl TokenSource for ProviderTokenSource {
+        async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(format!("token-{}", self.0.fetch_add(1, Ordering::SeqCst) + 1))
+        }
+    }
+
+    #[async_std::test]
+    async fn test_record_and_replay() {
+        let fixture = std::env::temp_dir().join(format!("reqwest-auth-replay-{}.txt", std::process::id()));
+
+        // Given - a recording of the tokens used by a client
+        let recording = Arc::new(RecordingTokenSource::new(Arc::new(ProviderTokenSource::default())));
+        let client = ClientBuilder::new(reqwest::Client::default())
+            .with(AuthorizationHeaderMiddleware::from(recording.clone()))
+            .build();
+        for _ in 0..2 {
+            // The requests fail without server, but their tokens are recorded anyway
+            let _ = client.get("http://127.0.0.1:1").send().await;
+        }
+        recording.save(&fixture).unwrap();
+
+        // When - replaying it
+        let replay = ReplayTokenSource::from_file(&fixture).unwrap();
+        std::fs::remove_file(&fixture).unwrap();
+
+        // Then - the same tokens are served, until exhausted
+        assert_eq!(recording.tokens(), ["token-1", "token-2"]);
+        assert_eq!(replay.token().await.unwrap(), "token-1");
+        assert_eq!(replay.token().await.unwrap(), "token-2");
+        assert_eq!(
+            replay.token().await.unwrap_err().to_string(),
+            "no recorded token left, 2 were recorded"
+        );
+    }
+}
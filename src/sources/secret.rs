@@ -0,0 +1,66 @@
+use secrecy::{ExposeSecret, SecretString};
+use std::fmt::{Debug, Formatter};
+use token_source::TokenSource;
+
+/// SecretTokenSource
+///
+/// A token source providing a static token, kept in a [SecretString] so that it is zeroized on drop.
+///
+/// The token is only exposed while authorizing a request, to build the header value.
+///
+/// Available with the `secrecy` feature.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, SecretTokenSource};
+///  use secrecy::SecretString;
+///  use std::sync::Arc;
+///
+///  let token = SecretString::from(std::env::var("API_TOKEN").unwrap_or_default());
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(Arc::new(SecretTokenSource::new(token)))
+///    .scheme("Bearer")
+///    .build();
+/// ```
+pub struct SecretTokenSource {
+    token: SecretString,
+}
+
+impl SecretTokenSource {
+    /// Creates a source providing the given token.
+    pub fn new(token: SecretString) -> Self {
+        Self { token }
+    }
+}
+
+impl Debug for SecretTokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Never show the token
+        f.debug_struct("SecretTokenSource").finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for SecretTokenSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.token.expose_secret().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::SecretString;
+    use token_source::TokenSource;
+
+    use super::SecretTokenSource;
+
+    #[async_std::test]
+    async fn test_secret() {
+        // Given - a source holding a secret token
+        let ts = SecretTokenSource::new(SecretString::from("s3cr3t"));
+
+        // Then - the token is provided, but never shown
+        assert_eq!(ts.token().await.unwrap(), "s3cr3t");
+        assert!(!format!("{ts:?}").contains("s3cr3t"));
+    }
+}
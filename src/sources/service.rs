@@ -0,0 +1,134 @@
+use std::fmt::{Debug, Formatter};
+use std::future::poll_fn;
+use std::sync::Mutex;
+use token_source::TokenSource;
+use tower_service::Service;
+
+/// ServiceTokenSource
+///
+/// A token source obtaining its tokens from a [tower service](tower_service::Service),
+/// for token fetching already implemented as a service (e.g with its own retry or rate limit layers).
+///
+/// The service is cloned for every token, then waited until ready before being called, as per the tower
+/// conventions. Its errors are reported as token source errors.
+///
+/// Available with the `tower` feature.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, ServiceTokenSource};
+///  use std::convert::Infallible;
+///  use std::future::{ready, Ready};
+///  use std::sync::Arc;
+///  use std::task::{Context, Poll};
+///
+///  #[derive(Clone)]
+///  struct MyTokenService;
+///
+///  impl tower_service::Service<()> for MyTokenService {
+///    type Response = String;
+///    type Error = Infallible;
+///    type Future = Ready<Result<String, Infallible>>;
+///
+///    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+///      Poll::Ready(Ok(()))
+///    }
+///
+///    fn call(&mut self, _req: ()) -> Self::Future {
+///      ready(Ok("my-token".to_string()))
+///    }
+///  }
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::from(Arc::new(ServiceTokenSource::new(MyTokenService)));
+/// ```
+pub struct ServiceTokenSource<S> {
+    service: Mutex<S>,
+}
+
+impl<S> ServiceTokenSource<S> {
+    /// Creates a source calling the given service.
+    pub fn new(service: S) -> Self {
+        Self {
+            service: Mutex::new(service),
+        }
+    }
+}
+
+impl<S> Debug for ServiceTokenSource<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServiceTokenSource").finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> TokenSource for ServiceTokenSource<S>
+where
+    S: Service<(), Response = String> + Clone + Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    S::Future: Send,
+{
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut service = self.service.lock().unwrap().clone();
+        poll_fn(|cx| service.poll_ready(cx)).await.map_err(Into::into)?;
+        service.call(()).await.map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{ready, Ready};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use token_source::TokenSource;
+    use tower_service::Service;
+
+    use super::ServiceTokenSource;
+
+    /// A service becoming ready on the second poll, then failing after two tokens.
+    #[derive(Clone, Default)]
+    struct TokenService {
+        polls: Arc<AtomicUsize>,
+        ready: Arc<AtomicBool>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Service<()> for TokenService {
+        type Response = String;
+        type Error = String;
+        type Future = Ready<Result<String, String>>;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), String>> {
+            // Pending on every other poll
+            self.polls.fetch_add(1, Ordering::SeqCst);
+            if !self.ready.fetch_xor(true, Ordering::SeqCst) {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _req: ()) -> Self::Future {
+            match self.calls.fetch_add(1, Ordering::SeqCst) + 1 {
+                count @ 1..=2 => ready(Ok(format!("token-{count}"))),
+                _ => ready(Err("service exhausted".to_string())),
+            }
+        }
+    }
+
+    #[async_std::test]
+    async fn test_service() {
+        // Given - a source wrapping a service which is not immediately ready
+        let service = TokenService::default();
+        let ts = ServiceTokenSource::new(service.clone());
+
+        // Then - the service is waited for, and called for every token
+        assert_eq!(ts.token().await.unwrap(), "token-1");
+        assert_eq!(ts.token().await.unwrap(), "token-2");
+        assert_eq!(service.polls.load(Ordering::SeqCst), 4);
+
+        // Then - its errors are reported
+        assert_eq!(ts.token().await.unwrap_err().to_string(), "service exhausted");
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use token_source::TokenSource;
+
+use crate::ContextualTokenSource;
+use crate::TokenContext;
+
+type VersionExtractor = Arc<dyn Fn(&TokenContext<'_>) -> Option<String> + Send + Sync>;
+
+/// VersionedTokenSource
+///
+/// A [ContextualTokenSource] picking the token source of the API version targeted by the request,
+/// for versioned APIs requiring different credentials per version.
+///
+/// Requests without a version, or with a version without source, get their token from the default source
+/// if any, and are sent without authorization otherwise.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, VersionedTokenSource};
+///  use std::sync::Arc;
+///  # use token_source::TokenSource;
+///  # #[derive(Debug)]
+///  # struct MyTokenSource;
+///  # #[async_trait::async_trait]
+///  # impl TokenSource for MyTokenSource {
+///  #   async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///  #      Ok("my-token".to_string())
+///  #   }
+///  # }
+///
+///  // Requests to /v1/... and /v2/... use different credentials
+///  let ts = VersionedTokenSource::from_path()
+///    .version("v1", Arc::new(MyTokenSource))
+///    .version("v2", Arc::new(MyTokenSource));
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::contextual_builder(Arc::new(ts)).build();
+/// ```
+pub struct VersionedTokenSource {
+    extractor: VersionExtractor,
+    sources: HashMap<String, Arc<dyn TokenSource>>,
+    default: Option<Arc<dyn TokenSource>>,
+}
+
+impl VersionedTokenSource {
+    /// Creates a source extracting the version of the requests with the given closure.
+    pub fn new<F>(extractor: F) -> Self
+    where
+        F: Fn(&TokenContext<'_>) -> Option<String> + Send + Sync + 'static,
+    {
+        Self {
+            extractor: Arc::new(extractor),
+            sources: HashMap::new(),
+            default: None,
+        }
+    }
+
+    /// Creates a source extracting the version from the first segment of the request path,
+    /// when it looks like a version (e.g `v1` in `/v1/items`).
+    pub fn from_path() -> Self {
+        Self::new(|ctx| {
+            let segment = ctx.url().path_segments()?.next()?;
+            let number = segment.strip_prefix('v').or_else(|| segment.strip_prefix('V'))?;
+            let is_version = !number.is_empty() && number.chars().all(|c| c.is_ascii_digit() || c == '.');
+            is_version.then(|| segment.to_string())
+        })
+    }
+
+    /// Sets the token source of the given version.
+    pub fn version(mut self, version: impl Into<String>, ts: Arc<dyn TokenSource>) -> Self {
+        self.sources.insert(version.into(), ts);
+        self
+    }
+
+    /// Sets the token source of the requests without version, or with a version without source.
+    ///
+    /// By default, those requests are sent without authorization.
+    pub fn default_source(mut self, ts: Arc<dyn TokenSource>) -> Self {
+        self.default = Some(ts);
+        self
+    }
+}
+
+impl Debug for VersionedTokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VersionedTokenSource")
+            .field("sources", &self.sources)
+            .field("default", &self.default)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl ContextualTokenSource for VersionedTokenSource {
+    async fn token_with(
+        &self,
+        ctx: &TokenContext<'_>,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let ts = (self.extractor)(ctx)
+            .and_then(|version| self.sources.get(&version))
+            .or(self.default.as_ref());
+        match ts {
+            Some(ts) => ts.token().await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::{Method, Url};
+    use std::sync::Arc;
+    use token_source::TokenSource;
+
+    use super::VersionedTokenSource;
+    use crate::{ContextualTokenSource, TokenContext};
+
+    #[derive(Debug)]
+    struct StaticTokenSource(&'static str);
+
+    #[async_trait::async_trait]
+    impl TokenSource for StaticTokenSource {
+        async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    async fn token(ts: &VersionedTokenSource, url: &str) -> Option<String> {
+        let url = Url::parse(url).unwrap();
+        let ctx = TokenContext {
+            method: &Method::GET,
+            url: &url,
+            value: None,
+            challenge: None,
+            stamp: None,
+        };
+        ts.token_with(&ctx).await.unwrap()
+    }
+
+    #[async_std::test]
+    async fn test_versioned() {
+        // Given - a source per version
+        let ts = VersionedTokenSource::from_path()
+            .version("v1", Arc::new(StaticTokenSource("v1-token")))
+            .version("v2", Arc::new(StaticTokenSource("v2-token")));
+
+        // Then - requests get the token of their version, others none
+        assert_eq!(token(&ts, "https://example.com/v1/items").await.unwrap(), "v1-token");
+        assert_eq!(token(&ts, "https://example.com/v2/items").await.unwrap(), "v2-token");
+        assert_eq!(token(&ts, "https://example.com/v3/items").await, None);
+        assert_eq!(token(&ts, "https://example.com/videos").await, None);
+
+        // When - setting a default source
+        // Then - requests without matching version get its token
+        let ts = ts.default_source(Arc::new(StaticTokenSource("default-token")));
+        assert_eq!(token(&ts, "https://example.com/v3/items").await.unwrap(), "default-token");
+        assert_eq!(token(&ts, "https://example.com/v1/items").await.unwrap(), "v1-token");
+    }
+}
@@ -0,0 +1,249 @@
+use reqwest_middleware::reqwest::StatusCode;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::sync::Mutex;
+use token_source::TokenSource;
+
+/// An API key, its weight and the outcomes of its recent requests (true when the response was unhealthy).
+struct WeightedKey {
+    key: String,
+    weight: u32,
+    outcomes: Mutex<VecDeque<bool>>,
+}
+
+/// WeightedKeysSource
+///
+/// A token source spreading the requests over a set of API keys, per their weights and their health, for rate
+/// limited APIs granting a quota per key.
+///
+/// Each token is drawn at random among the keys, with a probability proportional to their effective weight:
+///
+/// `effective weight = weight * max(1 - unhealthy rate, minimum weight factor)`
+///
+/// where the unhealthy rate is the share of the recent responses to the requests sent with the key (the last
+/// [health window](Self::health_window) ones) having an [unhealthy status](Self::unhealthy_statuses). A key
+/// rejected or throttled loses its share of the requests to the others, down to the [minimum weight
+/// factor](Self::min_weight_factor), which keeps probing it: it regains its share once its responses are healthy
+/// again. Keys without any response yet are healthy.
+///
+/// The responses are observed by the middleware with the [key_health](crate::AuthorizationHeaderMiddlewareBuilder::key_health)
+/// option, or through [observe](Self::observe). Without a [cache strategy](crate::CacheStrategy), each request
+/// gets its own key; with one, a key is drawn on each refresh.
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{AuthorizationHeaderMiddleware, WeightedKeysSource};
+///  use std::sync::Arc;
+///
+///  // The second key has twice the quota of the first one
+///  let keys = Arc::new(WeightedKeysSource::new([("first-key", 1), ("second-key", 2)]));
+///
+///  let auth_middleware = AuthorizationHeaderMiddleware::builder(keys.clone())
+///    .key_health(keys)
+///    .build();
+/// ```
+pub struct WeightedKeysSource {
+    keys: Vec<WeightedKey>,
+    window: usize,
+    unhealthy_statuses: Vec<StatusCode>,
+    min_weight_factor: f64,
+    rng: Mutex<fastrand::Rng>,
+}
+
+impl WeightedKeysSource {
+    /// Creates a source with the given keys and their weights; keys with a zero weight are never drawn.
+    pub fn new<K: Into<String>>(keys: impl IntoIterator<Item = (K, u32)>) -> Self {
+        Self {
+            keys: keys
+                .into_iter()
+                .map(|(key, weight)| WeightedKey {
+                    key: key.into(),
+                    weight,
+                    outcomes: Mutex::new(VecDeque::new()),
+                })
+                .collect(),
+            window: 20,
+            unhealthy_statuses: vec![StatusCode::UNAUTHORIZED, StatusCode::TOO_MANY_REQUESTS],
+            min_weight_factor: 0.05,
+            rng: Mutex::new(fastrand::Rng::new()),
+        }
+    }
+
+    /// Sets how many of the latest responses of each key make its health.
+    ///
+    /// A shorter window reacts faster, to both the failures and the recoveries, at the cost of noisier weights.
+    ///
+    /// Defaults to 20 responses.
+    pub fn health_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Sets the response statuses counted as unhealthy for the key they were sent with.
+    ///
+    /// Defaults to 401 (Unauthorized) and 429 (Too Many Requests).
+    pub fn unhealthy_statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.unhealthy_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Sets the share of its weight a fully unhealthy key keeps (between 0 and 1), so that it is still probed.
+    ///
+    /// With 0, a key whose responses in the window were all unhealthy is no longer drawn, so that it only recovers
+    /// through [observe](Self::observe). When no key has a positive effective weight, the keys are drawn per their
+    /// base weights.
+    ///
+    /// Defaults to 0.05.
+    pub fn min_weight_factor(mut self, factor: f64) -> Self {
+        self.min_weight_factor = factor.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Seeds the random draws of the keys, e.g for reproducible tests.
+    ///
+    /// By default, the draws are seeded randomly.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Mutex::new(fastrand::Rng::with_seed(seed));
+        self
+    }
+
+    /// Records the status of a response to a request sent with the given key; other tokens are ignored.
+    pub fn observe(&self, key: &str, status: StatusCode) {
+        if let Some(position) = self.position(key) {
+            self.record(position, status);
+        }
+    }
+
+    /// Returns the effective weights of the keys, in their order, to diagnose their health.
+    pub fn effective_weights(&self) -> Vec<f64> {
+        self.keys.iter().map(|key| self.effective_weight(key)).collect()
+    }
+
+    /// Returns the position of the given key (if any).
+    pub(crate) fn position(&self, key: &str) -> Option<usize> {
+        self.keys.iter().position(|weighted| weighted.key == key)
+    }
+
+    /// Records the status of a response to a request sent with the key at the given position.
+    pub(crate) fn record(&self, position: usize, status: StatusCode) {
+        let mut outcomes = self.keys[position].outcomes.lock().unwrap();
+        outcomes.push_back(self.unhealthy_statuses.contains(&status));
+        while outcomes.len() > self.window {
+            outcomes.pop_front();
+        }
+    }
+
+    fn effective_weight(&self, key: &WeightedKey) -> f64 {
+        let outcomes = key.outcomes.lock().unwrap();
+        let unhealthy = outcomes.iter().filter(|unhealthy| **unhealthy).count();
+        let rate = match outcomes.len() {
+            0 => 0.0,
+            len => unhealthy as f64 / len as f64,
+        };
+        f64::from(key.weight) * (1.0 - rate).max(self.min_weight_factor)
+    }
+
+    /// Draws a key per the effective weights, falling back to the base weights when no key is healthy enough.
+    fn draw(&self) -> Option<&str> {
+        let weights = self.effective_weights();
+        let weights = match weights.iter().sum::<f64>() > 0.0 {
+            true => weights,
+            false => self.keys.iter().map(|key| f64::from(key.weight)).collect(),
+        };
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = self.rng.lock().unwrap().f64() * total;
+        let key = self.keys.iter().zip(&weights).find(|(_, weight)| {
+            target -= **weight;
+            **weight > 0.0 && target < 0.0
+        });
+        // Rounding may leave the target past the last positive weight
+        let key = key.or_else(|| self.keys.iter().zip(&weights).rev().find(|(_, weight)| **weight > 0.0));
+        key.map(|(key, _)| key.key.as_str())
+    }
+}
+
+impl Debug for WeightedKeysSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeightedKeysSource")
+            .field("weights", &self.keys.iter().map(|key| key.weight).collect::<Vec<_>>())
+            .field("window", &self.window)
+            .field("unhealthy_statuses", &self.unhealthy_statuses)
+            .field("min_weight_factor", &self.min_weight_factor)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenSource for WeightedKeysSource {
+    async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        self.draw()
+            .map(str::to_string)
+            .ok_or_else(|| "No API key with a positive weight".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest_middleware::reqwest::StatusCode;
+    use token_source::TokenSource;
+
+    use super::WeightedKeysSource;
+
+    async fn draws(ts: &WeightedKeysSource, count: usize) -> [usize; 2] {
+        let mut draws = [0, 0];
+        for _ in 0..count {
+            match ts.token().await.unwrap().as_str() {
+                "first-key" => draws[0] += 1,
+                _ => draws[1] += 1,
+            }
+        }
+        draws
+    }
+
+    #[async_std::test]
+    async fn test_weighted_keys() {
+        // Given - two keys, the second one with three times the weight of the first one
+        let ts = WeightedKeysSource::new([("first-key", 1), ("second-key", 3)])
+            .health_window(10)
+            .seed(7);
+
+        // When - drawing keys
+        // Then - they are drawn per their weights
+        let [first, second] = draws(&ts, 1000).await;
+        assert!((200..300).contains(&first), "{first} draws of the first key");
+        assert_eq!(first + second, 1000);
+
+        // When - the second key is throttled
+        for _ in 0..10 {
+            ts.observe("second-key", StatusCode::TOO_MANY_REQUESTS);
+        }
+        ts.observe("unknown-key", StatusCode::TOO_MANY_REQUESTS);
+
+        // Then - it keeps the minimum share of its weight
+        assert_eq!(ts.effective_weights(), [1.0, 3.0 * 0.05]);
+        let [first, _] = draws(&ts, 1000).await;
+        assert!(first > 800, "{first} draws of the first key");
+
+        // When - the second key recovers
+        for _ in 0..10 {
+            ts.observe("second-key", StatusCode::OK);
+        }
+
+        // Then - it regains its weight
+        assert_eq!(ts.effective_weights(), [1.0, 3.0]);
+    }
+
+    #[async_std::test]
+    async fn test_weighted_keys_without_weight() {
+        // Given - keys without any weight
+        let ts = WeightedKeysSource::new([("first-key", 0)]);
+
+        // When - drawing a key
+        // Then - it fails
+        assert!(ts.token().await.is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// A token saved in a [TokenCache], along with when it expires.
+#[derive(Clone, PartialEq, Eq)]
+pub struct StoredToken {
+    /// The token value.
+    pub token: String,
+    /// When the token expires (wall clock time, comparable across processes), none if it never does.
+    pub expires_at: Option<SystemTime>,
+}
+
+impl Debug for StoredToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never show the token
+        f.debug_struct("StoredToken")
+            .field("expires_at", &self.expires_at)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StoredToken {
+    /// Returns whether the token expired at the given time.
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// TokenCache
+///
+/// A storage for the cached tokens, set with [token_cache](crate::AuthorizationHeaderMiddlewareBuilder::token_cache),
+/// e.g shared across the processes of a deployment (Redis, a file) so that they do not each fetch their own token.
+///
+/// The middleware keeps its in-memory cache, and goes through the storage when it misses: a valid stored token is
+/// used as is, otherwise the token fetched from the token source is saved for the others. Entries are identified
+/// by a key derived from the namespace given to the middleware:
+///
+/// - the token of the middleware token source is saved under the namespace itself (e.g `my-service`),
+/// - the contextual tokens with a [cache key](crate::AuthorizationHeaderMiddlewareBuilder::cache_key) are saved
+///   under the namespace followed by the parts of their key, each one prefixed with a `:` (e.g
+///   `my-service:api.example.com:orders:read`), the `%` and `:` of the parts being escaped as `%25` and `%3A`.
+///
+/// Failing loads and saves are logged, and fall back to the token source: the storage is an optimization, never
+/// a reason to fail a request.
+///
+/// <div class="warning">The stored tokens are secret material: the storage should be protected as the credentials
+/// themselves are.</div>
+///
+/// # How to use
+///
+/// ```rust
+///  use reqwest_auth::{StoredToken, TokenCache};
+///
+///  #[derive(Debug)]
+///  struct RedisTokenCache;
+///
+///  #[async_trait::async_trait]
+///  impl TokenCache for RedisTokenCache {
+///    async fn load(&self, key: &str) -> Result<Option<StoredToken>, Box<dyn std::error::Error + Send + Sync>> {
+///      // GET the key, and decode the token and its expiry
+///      Ok(None)
+///    }
+///
+///    async fn save(&self, key: &str, token: &StoredToken) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+///      // SET the key, expiring with the token
+///      Ok(())
+///    }
+///  }
+/// ```
+#[async_trait::async_trait]
+pub trait TokenCache: Send + Sync + Debug {
+    /// Returns the token saved under the given key, if any.
+    ///
+    /// Expired tokens may be returned: the middleware ignores them.
+    async fn load(&self, key: &str) -> Result<Option<StoredToken>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Saves the given token under the given key, replacing the previous one (if any).
+    async fn save(&self, key: &str, token: &StoredToken) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// MemoryTokenCache
+///
+/// A [TokenCache] keeping the tokens in memory, e.g to share them across the middlewares of a process.
+#[derive(Debug, Default)]
+pub struct MemoryTokenCache {
+    tokens: Mutex<HashMap<String, StoredToken>>,
+}
+
+impl MemoryTokenCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenCache for MemoryTokenCache {
+    async fn load(&self, key: &str) -> Result<Option<StoredToken>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.tokens.lock().unwrap().get(key).cloned())
+    }
+
+    async fn save(&self, key: &str, token: &StoredToken) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.tokens.lock().unwrap().insert(key.to_string(), token.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+//! Token acquisition spans, created through the OpenTelemetry global tracer with the `opentelemetry` feature.
+//!
+//! Without the feature, tracing is a no-op. The spans never record the tokens, nor the token source errors
+//! (which may quote them).
+
+use std::future::Future;
+
+/// The name of the span covering the acquisition of a token (from the cache or the token source).
+#[cfg(feature = "opentelemetry")]
+pub(crate) const TOKEN_ACQUISITION: &str = "reqwest_auth.token_acquisition";
+/// The effective host of the request being authorized.
+#[cfg(feature = "opentelemetry")]
+pub(crate) const HOST: &str = "auth.host";
+/// Whether the token was served from the cache (only set with a cache strategy).
+#[cfg(feature = "opentelemetry")]
+pub(crate) const CACHE_HIT: &str = "auth.cache_hit";
+/// The outcome of the acquisition (`success` or `failure`).
+#[cfg(feature = "opentelemetry")]
+pub(crate) const OUTCOME: &str = "auth.outcome";
+
+/// Traces the acquisition of a token for the given host (only computed when tracing), as a span of the current
+/// context.
+pub(crate) async fn acquire<T, E>(
+    host: impl FnOnce() -> Option<String>,
+    acquire: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    #[cfg(feature = "opentelemetry")]
+    {
+        use opentelemetry::context::FutureExt;
+        use opentelemetry::trace::{Span, Status, TraceContextExt, Tracer};
+        use opentelemetry::{Context, KeyValue};
+
+        let tracer = opentelemetry::global::tracer("reqwest-auth");
+        let mut span = tracer.start(TOKEN_ACQUISITION);
+        if let Some(host) = host() {
+            span.set_attribute(KeyValue::new(HOST, host));
+        }
+        let cx = Context::current_with_span(span);
+        let res = acquire.with_context(cx.clone()).await;
+        let span = cx.span();
+        match res {
+            Ok(_) => span.set_attribute(KeyValue::new(OUTCOME, "success")),
+            Err(_) => {
+                span.set_attribute(KeyValue::new(OUTCOME, "failure"));
+                span.set_status(Status::error("token acquisition failed"));
+            }
+        }
+        span.end();
+        res
+    }
+    #[cfg(not(feature = "opentelemetry"))]
+    {
+        let _ = host;
+        acquire.await
+    }
+}
+
+/// Records whether the token being acquired was served from the cache.
+pub(crate) fn cache_hit(hit: bool) {
+    #[cfg(feature = "opentelemetry")]
+    opentelemetry::trace::get_active_span(|span| span.set_attribute(opentelemetry::KeyValue::new(CACHE_HIT, hit)));
+    #[cfg(not(feature = "opentelemetry"))]
+    let _ = hit;
+}
+
+#[cfg(all(test, feature = "opentelemetry"))]
+mod tests {
+    use opentelemetry::{Key, Value};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+
+    use super::{acquire, cache_hit};
+
+    #[async_std::test]
+    async fn test_spans() {
+        // Given - a tracer provider keeping the spans in memory
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        opentelemetry::global::set_tracer_provider(provider);
+
+        // When - acquiring tokens, from the cache or not
+        let _ = acquire(|| Some("otel.example.com".to_string()), async {
+            cache_hit(true);
+            Ok::<_, ()>("my-token")
+        })
+        .await;
+        let _ = acquire(|| Some("otel.example.com".to_string()), async {
+            cache_hit(false);
+            Err::<(), _>("my-token is invalid")
+        })
+        .await;
+
+        // Then - a span is recorded per acquisition, with the standard attributes but never the token
+        let spans: Vec<_> = exporter
+            .get_finished_spans()
+            .unwrap()
+            .into_iter()
+            .filter(|span| {
+                span.attributes
+                    .iter()
+                    .any(|kv| kv.key.as_str() == "auth.host" && kv.value.as_str() == "otel.example.com")
+            })
+            .collect();
+        assert_eq!(spans.len(), 2);
+        let attribute = |i: usize, key: &'static str| {
+            spans[i]
+                .attributes
+                .iter()
+                .find(|kv| kv.key == Key::from_static_str(key))
+                .map(|kv| kv.value.clone())
+        };
+        assert_eq!(spans[0].name, "reqwest_auth.token_acquisition");
+        assert_eq!(attribute(0, "auth.cache_hit"), Some(Value::Bool(true)));
+        assert_eq!(attribute(0, "auth.outcome"), Some(Value::from("success")));
+        assert_eq!(attribute(1, "auth.cache_hit"), Some(Value::Bool(false)));
+        assert_eq!(attribute(1, "auth.outcome"), Some(Value::from("failure")));
+        assert!(!format!("{spans:?}").contains("my-token"));
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::Clock;
+
+/// AuthTiming
+///
+/// How long the middleware spent authorizing a request, placed in the extensions of its response with the
+/// [auth_timing](crate::AuthorizationHeaderMiddlewareBuilder::auth_timing) option.
+///
+/// The duration covers the authorizations of the request: obtaining the tokens (from the cache or the token
+/// sources) and setting the headers, for the request and its retries (e.g after a 401 response). The time spent
+/// by the next middlewares and the server is not included, which separates the authorization overhead from the
+/// backend latency. It is read from the [clock](crate::AuthorizationHeaderMiddlewareBuilder::clock) of the
+/// middleware.
+///
+/// # How to use
+///
+/// ```rust,no_run
+///  # async fn run(client: reqwest_middleware::ClientWithMiddleware) -> reqwest_middleware::Result<()> {
+///  use reqwest_auth::AuthTiming;
+///
+///  let res = client.get("https://example.com").send().await?;
+///  if let Some(timing) = res.extensions().get::<AuthTiming>() {
+///    println!("Authorized in {:?} ({} attempts)", timing.duration(), timing.attempts());
+///  }
+///  # Ok(())
+///  # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AuthTiming {
+    pub(crate) duration: Duration,
+    pub(crate) attempts: u32,
+}
+
+impl AuthTiming {
+    /// Returns the total time spent authorizing the request.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns how many times the request was authorized, more than once when it was retried.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+}
+
+/// Accumulates the authorization time of a request, placed in its extensions while it is being handled.
+#[derive(Clone, Default)]
+pub(crate) struct Timer(Arc<Mutex<AuthTiming>>);
+
+impl Timer {
+    /// Adds an authorization that started at the given instant, and just ended.
+    pub(crate) fn record(&self, clock: &dyn Clock, start: Instant) {
+        let mut timing = self.0.lock().unwrap();
+        timing.duration += clock.now().saturating_duration_since(start);
+        timing.attempts += 1;
+    }
+
+    /// Returns the recorded timing, none when the request was not authorized.
+    pub(crate) fn timing(&self) -> Option<AuthTiming> {
+        Some(*self.0.lock().unwrap()).filter(|timing| timing.attempts > 0)
+    }
+}